@@ -27,6 +27,7 @@
                 slurp
                 tesseract
                 hyprpicker
+                wayfreeze
                 wl-clipboard
                 wlr-randr # for mango
                 xdg-utils # xdg-open
@@ -11,10 +11,11 @@
   slurp,
   tesseract,
   hyprpicker,
+  wayfreeze,
   wf-recorder,
-  wl-clipboard,
   wlr-randr,
   xdg-utils,
+  zbar,
   ocr ? true,
   video ? true,
   focalWaybar ? true,
@@ -75,9 +76,10 @@ rustPlatform.buildRustPackage {
         rofi
         slurp
         hyprpicker
-        wl-clipboard
+        wayfreeze
         wlr-randr
         xdg-utils
+        zbar
       ]
       ++ lib.optionals video [
         ffmpeg
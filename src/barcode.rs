@@ -0,0 +1,130 @@
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use execute::Execute;
+
+use crate::clipboard::Clipboard;
+
+/// symbologies to look for when decoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Barcode {
+    Qr,
+    /// any barcode supported by zbar
+    Any,
+}
+
+impl Barcode {
+    fn command(self, image: &Path) -> Command {
+        let mut cmd = Command::new("zbarimg");
+        cmd.arg("--quiet").arg("--raw");
+
+        if self == Self::Qr {
+            cmd.arg("-Sdisable").arg("-Sqrcode.enable");
+        }
+
+        cmd.arg(image);
+        cmd
+    }
+
+    /// returns the payloads of the barcodes in the image
+    pub fn decode(self, image: &Path) -> Vec<String> {
+        let output = self
+            .command(image)
+            .stdout(Stdio::piped())
+            .execute_output()
+            .expect("Failed to run zbarimg");
+
+        // zbarimg exits with 4 if no barcodes were found
+        parse_payloads(&String::from_utf8_lossy(&output.stdout))
+    }
+}
+
+fn parse_payloads(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// urls can be opened from the notification
+fn is_url(payload: &str) -> bool {
+    ["http://", "https://"]
+        .iter()
+        .any(|scheme| payload.starts_with(scheme))
+        && !payload.contains(char::is_whitespace)
+}
+
+fn notify(payload: &str) {
+    let mut notification = notify_rust::Notification::new();
+    notification.appname("focal").body(payload).timeout(3000);
+
+    if is_url(payload) {
+        notification.action("open", "open");
+    }
+
+    let handle = notification.show().expect("Failed to send notification");
+
+    if is_url(payload) {
+        handle.wait_for_action(|action| {
+            if action == "open" {
+                Command::new("xdg-open")
+                    .arg(payload)
+                    .spawn()
+                    .expect("Failed to open url")
+                    .wait()
+                    .expect("Failed to wait for xdg-open");
+            }
+        });
+    }
+}
+
+/// decodes the barcodes in the image and copies the payloads to the clipboard
+pub fn decode(image: &Path, barcode: Barcode, primary: bool, show_notification: bool) {
+    let payloads = barcode.decode(image);
+
+    if payloads.is_empty() {
+        let msg = match barcode {
+            Barcode::Qr => "No QR code found in screenshot",
+            Barcode::Any => "No barcode found in screenshot",
+        };
+        eprintln!("{msg}");
+        if show_notification {
+            crate::show_notification(msg, None);
+        }
+        return;
+    }
+
+    let text = payloads.join("\n");
+    println!("{text}");
+
+    Clipboard::new().primary(primary).text(&text).copy();
+
+    if show_notification {
+        notify(&text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_payloads() {
+        assert_eq!(
+            parse_payloads("https://example.com\n\nWIFI:S:focal;;\r\n"),
+            ["https://example.com", "WIFI:S:focal;;"]
+        );
+        assert!(parse_payloads("").is_empty());
+
+        assert!(is_url("https://example.com"));
+        assert!(!is_url("WIFI:S:focal;;"));
+        assert!(
+            !is_url("https://example.com\nhttps://example.org"),
+            "multiple payloads cannot be opened"
+        );
+    }
+}
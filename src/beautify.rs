@@ -0,0 +1,244 @@
+use clap::CommandFactory;
+use image::{DynamicImage, GrayImage, Luma, Rgba, RgbaImage, imageops};
+
+use crate::cli::{focal::Cli, image::BeautifyArgs};
+
+pub const DEFAULT_PADDING: u32 = 64;
+pub const DEFAULT_BACKGROUND: &str = "#1e1e2e:#585b70";
+pub const DEFAULT_RADIUS: u32 = 12;
+pub const DEFAULT_SHADOW: u32 = 24;
+
+/// opacity of the drop shadow
+const SHADOW_OPACITY: f32 = 0.5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Background {
+    Solid(Rgba<u8>),
+    /// diagonal gradient from the top left to the bottom right
+    Gradient(Rgba<u8>, Rgba<u8>),
+}
+
+/// parses a color in the form of #RRGGBB or #RRGGBBAA
+pub fn parse_color(color: &str) -> Result<Rgba<u8>, String> {
+    let hex = color.strip_prefix('#').unwrap_or(color);
+
+    let channel = |idx: usize| {
+        hex.get(idx..idx + 2)
+            .and_then(|c| u8::from_str_radix(c, 16).ok())
+            .ok_or_else(|| format!("Invalid color {color}, expected #RRGGBB or #RRGGBBAA"))
+    };
+
+    match hex.len() {
+        6 => Ok(Rgba([channel(0)?, channel(2)?, channel(4)?, 255])),
+        8 => Ok(Rgba([channel(0)?, channel(2)?, channel(4)?, channel(6)?])),
+        _ => Err(format!(
+            "Invalid color {color}, expected #RRGGBB or #RRGGBBAA"
+        )),
+    }
+}
+
+impl std::str::FromStr for Background {
+    type Err = String;
+
+    /// a single color or two colors separated by : for a gradient
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some((start, end)) => Ok(Self::Gradient(parse_color(start)?, parse_color(end)?)),
+            None => Ok(Self::Solid(parse_color(s)?)),
+        }
+    }
+}
+
+impl Background {
+    fn render(&self, w: u32, h: u32) -> RgbaImage {
+        match self {
+            Self::Solid(color) => RgbaImage::from_pixel(w, h, *color),
+            Self::Gradient(start, end) => {
+                #[allow(clippy::cast_precision_loss)]
+                let max = (w + h).saturating_sub(2).max(1) as f32;
+
+                RgbaImage::from_fn(w, h, |x, y| {
+                    #[allow(clippy::cast_precision_loss)]
+                    let t = (x + y) as f32 / max;
+                    lerp(*start, *end, t)
+                })
+            }
+        }
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn lerp(start: Rgba<u8>, end: Rgba<u8>, t: f32) -> Rgba<u8> {
+    Rgba(std::array::from_fn(|i| {
+        f32::from(end[i])
+            .mul_add(t, f32::from(start[i]) * (1.0 - t))
+            .round() as u8
+    }))
+}
+
+/// fraction of the pixel at (x, y) that lies inside a rectangle of width x height with rounded corners
+#[allow(clippy::cast_precision_loss)]
+fn corner_coverage(x: u32, y: u32, width: u32, height: u32, radius: u32) -> f32 {
+    let radius = radius.min(width / 2).min(height / 2) as f32;
+    let (width, height) = (width as f32, height as f32);
+
+    // center of the corner circle nearest to the pixel center, if the pixel is within a corner
+    let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+    let cx = if px < radius {
+        radius
+    } else if px > width - radius {
+        width - radius
+    } else {
+        return 1.0;
+    };
+    let cy = if py < radius {
+        radius
+    } else if py > height - radius {
+        height - radius
+    } else {
+        return 1.0;
+    };
+
+    let dist = (px - cx).hypot(py - cy);
+    (radius - dist + 0.5).clamp(0.0, 1.0)
+}
+
+#[derive(Debug, Clone)]
+pub struct Beautify {
+    pub padding: u32,
+    pub background: Background,
+    pub radius: u32,
+    pub shadow: u32,
+}
+
+impl Beautify {
+    /// creates the beautify options if --beautify is given, exiting if the background is invalid
+    pub fn from_args(args: &BeautifyArgs) -> Option<Self> {
+        if !args.beautify {
+            return None;
+        }
+
+        let background = args
+            .background
+            .as_deref()
+            .unwrap_or(DEFAULT_BACKGROUND)
+            .parse()
+            .unwrap_or_else(|err: String| {
+                Cli::command()
+                    .error(clap::error::ErrorKind::InvalidValue, err)
+                    .exit()
+            });
+
+        Some(Self {
+            padding: args.padding.unwrap_or(DEFAULT_PADDING),
+            background,
+            radius: args.radius.unwrap_or(DEFAULT_RADIUS),
+            shadow: args.shadow.unwrap_or(DEFAULT_SHADOW),
+        })
+    }
+
+    /// rounds the corners of the image by making them transparent
+    fn round_corners(&self, img: &mut RgbaImage) {
+        if self.radius == 0 {
+            return;
+        }
+
+        let (w, h) = img.dimensions();
+        for (x, y, pixel) in img.enumerate_pixels_mut() {
+            let coverage = corner_coverage(x, y, w, h, self.radius);
+            if coverage < 1.0 {
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let alpha = (f32::from(pixel[3]) * coverage).round() as u8;
+                pixel[3] = alpha;
+            }
+        }
+    }
+
+    /// draws a blurred shadow of the image onto the canvas
+    fn draw_shadow(&self, canvas: &mut RgbaImage, img: &RgbaImage) {
+        if self.shadow == 0 {
+            return;
+        }
+
+        // the shadow is offset downwards slightly
+        let mut mask = GrayImage::new(canvas.width(), canvas.height());
+        let offset = self.shadow / 4;
+        for (x, y, pixel) in img.enumerate_pixels() {
+            let (mx, my) = (x + self.padding, y + self.padding + offset);
+            if mx < mask.width() && my < mask.height() {
+                mask.put_pixel(mx, my, Luma([pixel[3]]));
+            }
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let mask = imageops::fast_blur(&mask, self.shadow as f32 / 2.0);
+
+        for (pixel, shadow) in canvas.pixels_mut().zip(mask.pixels()) {
+            let alpha = f32::from(shadow[0]) / 255.0 * SHADOW_OPACITY;
+            *pixel = lerp(*pixel, Rgba([0, 0, 0, pixel[3]]), alpha);
+        }
+    }
+
+    pub fn apply(&self, img: &DynamicImage) -> DynamicImage {
+        let mut img = img.to_rgba8();
+        self.round_corners(&mut img);
+
+        let mut canvas = self.background.render(
+            img.width() + 2 * self.padding,
+            img.height() + 2 * self.padding,
+        );
+
+        self.draw_shadow(&mut canvas, &img);
+
+        imageops::overlay(&mut canvas, &img, self.padding.into(), self.padding.into());
+
+        canvas.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_background() {
+        assert_eq!(
+            "#ff000080".parse(),
+            Ok(Background::Solid(Rgba([255, 0, 0, 128])))
+        );
+        assert_eq!(
+            "000000:#ffffff".parse(),
+            Ok(Background::Gradient(
+                Rgba([0, 0, 0, 255]),
+                Rgba([255, 255, 255, 255])
+            ))
+        );
+        assert!("#fff".parse::<Background>().is_err());
+        assert!("#gggggg".parse::<Background>().is_err());
+    }
+
+    #[test]
+    fn test_apply() {
+        let img = DynamicImage::from(RgbaImage::from_pixel(100, 50, Rgba([255, 255, 255, 255])));
+        let beautify = Beautify {
+            padding: 10,
+            background: Background::Solid(Rgba([255, 0, 0, 255])),
+            radius: 8,
+            shadow: 0,
+        }
+        .apply(&img)
+        .to_rgba8();
+
+        assert_eq!(beautify.dimensions(), (120, 70), "padding should be added");
+        assert_eq!(
+            beautify.get_pixel(10, 10),
+            &Rgba([255, 0, 0, 255]),
+            "rounded corners should show the background"
+        );
+        assert_eq!(
+            beautify.get_pixel(60, 35),
+            &Rgba([255, 255, 255, 255]),
+            "center should be the original image"
+        );
+    }
+}
@@ -0,0 +1,72 @@
+use clap::{Args, ValueEnum};
+
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorFormat {
+    /// #rrggbb
+    #[default]
+    Hex,
+    /// rgb(r, g, b)
+    Rgb,
+    /// hsl(h, s%, l%)
+    Hsl,
+}
+
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Args, Debug)]
+pub struct ColorArgs {
+    #[arg(
+        short,
+        long,
+        value_enum,
+        default_value_t,
+        help = "Format the color is printed and copied as"
+    )]
+    pub format: ColorFormat,
+
+    #[arg(
+        long,
+        action,
+        overrides_with = "no_freeze",
+        help = "Freeze the screen before picking a color with slurp"
+    )]
+    pub freeze: bool,
+
+    #[arg(long, action, hide = true, overrides_with = "freeze")]
+    pub no_freeze: bool,
+
+    #[arg(
+        long,
+        action,
+        help = "Always pick with slurp and grim instead of the compositor's picker"
+    )]
+    pub no_native: bool,
+
+    #[arg(
+        long,
+        action,
+        overrides_with = "notify",
+        help = "Do not show notifications"
+    )]
+    pub no_notify: bool,
+
+    #[arg(long, action, hide = true, overrides_with = "no_notify")]
+    pub notify: bool,
+
+    #[arg(
+        long,
+        action,
+        overrides_with = "no_primary",
+        help = "Also copy to the primary selection"
+    )]
+    pub primary: bool,
+
+    #[arg(long, action, hide = true, overrides_with = "primary")]
+    pub no_primary: bool,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Use a named profile from the config file"
+    )]
+    pub profile: Option<String>,
+}
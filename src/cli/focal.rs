@@ -13,8 +13,47 @@ pub enum FocalSubcommand {
     #[command(name = "video", about = "Captures a video.")]
     Video(super::video::VideoArgs),
 
+    #[command(name = "color", about = "Picks the color of a pixel on the screen.")]
+    Color(super::color::ColorArgs),
+
+    #[command(name = "history", about = "Lists previous captures.")]
+    History(super::history::HistoryArgs),
+
+    #[command(
+        name = "last",
+        about = "Performs an action on the most recent capture."
+    )]
+    Last(super::last::LastArgs),
+
+    #[command(
+        name = "process",
+        about = "Applies the post capture steps to an existing image."
+    )]
+    Process(super::process::ProcessArgs),
+
+    #[command(name = "recent", about = "Shows recent captures in a rofi menu.")]
+    Recent(super::recent::RecentArgs),
+
     #[command(name = "generate", about = "Generate shell completions", hide = true)]
     Generate(GenerateArgs),
+
+    /// serves the clipboard in the background, since copying multiple mime types is not possible with wl-copy
+    #[command(name = "clipboard-serve", hide = true)]
+    ClipboardServe,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyMode {
+    /// the file contents, along with a text/uri-list of the file
+    Image,
+    /// the path of the file as text
+    Path,
+    /// a file:// uri as a text/uri-list
+    Uri,
+    /// a markdown link to the file as text
+    Markdown,
+    /// nothing is copied
+    None,
 }
 
 #[derive(Subcommand, ValueEnum, Debug, Clone)]
@@ -30,6 +69,7 @@ pub struct GenerateArgs {
     pub shell: ShellCompletion,
 }
 
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Args, Debug)]
 pub struct CommonArgs {
     #[arg(short = 't', long, help = "Delay in seconds before capturing")]
@@ -41,15 +81,142 @@ pub struct CommonArgs {
     #[arg(
         long,
         hide = std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default() != "Hyprland",
+        overrides_with = "rounded_windows",
         help = "Do not show rounded corners when capturing a window."
     )]
     pub no_rounded_windows: bool,
 
-    #[arg(long, action, help = "Do not show notifications")]
+    #[arg(long, action, hide = true, overrides_with = "no_rounded_windows")]
+    pub rounded_windows: bool,
+
+    #[arg(
+        long,
+        action,
+        overrides_with = "notify",
+        help = "Do not show notifications"
+    )]
     pub no_notify: bool,
 
-    #[arg(long, action, help = "Do not save the file permanently")]
+    #[arg(long, action, hide = true, overrides_with = "no_notify")]
+    pub notify: bool,
+
+    #[arg(
+        long,
+        action,
+        overrides_with = "no_cursor",
+        help = "Include the mouse cursor in the capture",
+        long_help = "Include the mouse cursor in the capture\nDefaults to hiding the cursor for images and showing it for videos\nVideos always include the cursor, as wf-recorder cannot hide it, so --no-cursor and the cursor config key only apply to images"
+    )]
+    pub cursor: bool,
+
+    #[arg(
+        long,
+        action,
+        overrides_with = "cursor",
+        help = "Do not include the mouse cursor in the capture",
+        long_help = "Do not include the mouse cursor in the capture\nOnly supported for images, as wf-recorder always records the cursor"
+    )]
+    pub no_cursor: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        value_name = "MODE",
+        help = "What to copy to the clipboard, can be given multiple times",
+        long_help = "What to copy to the clipboard, can be given multiple times\nDefaults to image for screenshots and uri for videos\nWhen multiple modes offer text, the first one is used"
+    )]
+    pub copy: Vec<CopyMode>,
+
+    #[arg(
+        long,
+        action,
+        overrides_with = "no_primary",
+        help = "Also copy to the primary selection"
+    )]
+    pub primary: bool,
+
+    #[arg(long, action, hide = true, overrides_with = "primary")]
+    pub no_primary: bool,
+
+    #[arg(
+        long,
+        value_name = "COMMAND",
+        help = "Run COMMAND after capturing, can be given multiple times",
+        long_help = "Run COMMAND after capturing, can be given multiple times\nThe capture is passed as $FOCAL_PATH, along with $FOCAL_KIND, $FOCAL_AREA, $FOCAL_GEOMETRY, $FOCAL_MONITOR, $FOCAL_WINDOW_CLASS and $FOCAL_DURATION for videos"
+    )]
+    pub exec: Vec<String>,
+
+    #[arg(
+        long,
+        value_name = "COMMAND",
+        help = "Run COMMAND in the background after capturing, can be given multiple times"
+    )]
+    pub exec_background: Vec<String>,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Upload the capture and copy its url",
+        long_help = "Upload the capture and copy its url\nUploads are defined as [uploads.NAME] in $XDG_CONFIG_HOME/focal/config.toml"
+    )]
+    pub upload: Option<String>,
+
+    #[arg(
+        long,
+        action,
+        overrides_with = "save",
+        help = "Do not save the file permanently"
+    )]
     pub no_save: bool,
+
+    #[arg(long, action, hide = true, overrides_with = "no_save")]
+    pub save: bool,
+
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        help = "Template for generated filenames",
+        long_help = "Template for generated filenames, without the extension\nstrftime fields such as {%Y} and {%m} can be used along with {area}, {monitor}, {app}, {title}, {w}, {h} and {n} for a counter\nSubdirectories can be created using a template such as {%Y}/{%m}/{%d}-{n}"
+    )]
+    pub template: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Use a named profile from the config file",
+        long_help = "Use a named profile from the config file\nProfiles are defined as [profiles.NAME] in $XDG_CONFIG_HOME/focal/config.toml"
+    )]
+    pub profile: Option<String>,
+}
+
+/// the modes to copy, using the default if none are given and nothing for none
+pub fn copy_modes(copy: &[CopyMode], default: CopyMode) -> Vec<CopyMode> {
+    if copy.is_empty() {
+        vec![default]
+    } else if copy.contains(&CopyMode::None) {
+        Vec::new()
+    } else {
+        copy.to_vec()
+    }
+}
+
+impl CommonArgs {
+    /// what to copy to the clipboard, using the default if --copy is not given
+    pub fn copy_modes(&self, default: CopyMode) -> Vec<CopyMode> {
+        copy_modes(&self.copy, default)
+    }
+
+    /// if the cursor should be captured, using the default if neither --cursor or --no-cursor is given
+    pub const fn show_cursor(&self, default: bool) -> bool {
+        if self.cursor {
+            true
+        } else if self.no_cursor {
+            false
+        } else {
+            default
+        }
+    }
 }
 
 #[allow(clippy::module_name_repetitions)]
@@ -58,9 +225,17 @@ pub struct RofiArgs {
     #[arg(long, action, help = "Display rofi menu for selection options")]
     pub rofi: bool,
 
-    #[arg(long, action, help = "Do not show icons for rofi menu")]
+    #[arg(
+        long,
+        action,
+        overrides_with = "icons",
+        help = "Do not show icons for rofi menu"
+    )]
     pub no_icons: bool,
 
+    #[arg(long, action, hide = true, overrides_with = "no_icons")]
+    pub icons: bool,
+
     #[arg(long, action, help = "Path to a rofi theme")]
     pub theme: Option<PathBuf>,
 }
@@ -123,7 +298,27 @@ mod tests {
             "--ocr and --edit are exclusive",
         );
 
+        assert_cmd(
+            "focal image --each-monitor --ocr",
+            ErrorKind::ArgumentConflict,
+            "each monitor is saved separately, so it cannot be scanned",
+        );
+
         let res = Cli::try_parse_from("focal generate fish".split_whitespace());
         assert!(res.is_ok(), "generate should still work");
     }
+    #[test]
+    fn test_invalid_values() {
+        assert_cmd(
+            "focal image --area monitor --max-width 0",
+            ErrorKind::ValueValidation,
+            "--max-width should be at least 1",
+        );
+
+        assert_cmd(
+            "focal process --max-height 0 image.png",
+            ErrorKind::ValueValidation,
+            "--max-height should be at least 1",
+        );
+    }
 }
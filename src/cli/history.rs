@@ -0,0 +1,34 @@
+use clap::{Args, ValueEnum};
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryKind {
+    Image,
+    Video,
+}
+
+#[derive(Args, Debug)]
+pub struct HistoryArgs {
+    #[arg(long, action, help = "Output the history as json")]
+    pub json: bool,
+
+    #[arg(
+        short = 'n',
+        long,
+        value_name = "N",
+        help = "Only show the N most recent captures"
+    )]
+    pub limit: Option<usize>,
+
+    #[arg(long, value_enum, help = "Only show images or videos")]
+    pub kind: Option<HistoryKind>,
+
+    #[arg(long, help = "Only show captures of the given area")]
+    pub area: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "CLASS",
+        help = "Only show captures where the window class contains CLASS"
+    )]
+    pub app: Option<String>,
+}
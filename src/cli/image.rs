@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::cli::focal::{CommonArgs, RofiArgs};
 use clap::{ArgGroup, Args, Subcommand, ValueEnum};
@@ -9,13 +9,253 @@ pub enum CaptureArea {
     Selection,
     Window,
     All,
+    EachMonitor,
+}
+
+#[derive(ValueEnum, Debug, Clone, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Png,
+    #[value(alias = "jpg")]
+    Jpeg,
+    Webp,
+    Avif,
+    Qoi,
+}
+
+impl OutputFormat {
+    /// infers the format from the extension of a path
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?;
+        Self::from_str(ext, true).ok()
+    }
+
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg => "jpg",
+            Self::Webp => "webp",
+            Self::Avif => "avif",
+            Self::Qoi => "qoi",
+        }
+    }
+
+    pub const fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Jpeg => "image/jpeg",
+            Self::Webp => "image/webp",
+            Self::Avif => "image/avif",
+            Self::Qoi => "image/qoi",
+        }
+    }
+
+    /// if tesseract and zbarimg can read the format
+    pub const fn is_scannable(&self) -> bool {
+        matches!(self, Self::Png | Self::Jpeg)
+    }
+}
+
+fn parse_scale(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(scale) if scale > 0.0 => Ok(scale),
+        _ => Err("scale must be a number greater than 0".to_string()),
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OcrFormat {
+    /// plain text
+    #[default]
+    Text,
+    /// html with the position of each word
+    Hocr,
+    /// tab separated values with the position and confidence of each word
+    Tsv,
+    /// paragraphs, headings and lists detected from the layout
+    Markdown,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollInput {
+    /// scrolls the window under the cursor with the mouse wheel
+    Ydotool,
+    /// presses the down arrow key in the focused window
+    Wtype,
+}
+
+impl ScrollInput {
+    pub const fn program(self) -> &'static str {
+        match self {
+            Self::Ydotool => "ydotool",
+            Self::Wtype => "wtype",
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OcrCleanup {
+    /// joins the lines of each paragraph
+    JoinLines,
+    /// joins words hyphenated across lines
+    Dehyphenate,
+    /// collapses repeated spaces and blank lines
+    Whitespace,
+}
+
+// prefixed to match the flags, which are shared with ImageArgs
+#[allow(clippy::struct_field_names)]
+#[derive(Args, Debug)]
+pub struct OcrArgs {
+    #[arg(
+        long,
+        value_name = "MODE",
+        value_parser = clap::value_parser!(u8).range(0..=13),
+        help = "Tesseract page segmentation mode",
+        long_help = "Tesseract page segmentation mode from 0-13\nModes can be shown using 'tesseract --help-psm'",
+        hide = cfg!(not(feature = "ocr"))
+    )]
+    pub ocr_psm: Option<u8>,
+
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        help = "Output format of the recognized text",
+        hide = cfg!(not(feature = "ocr"))
+    )]
+    pub ocr_format: Option<OcrFormat>,
+
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        value_name = "CLEANUP",
+        help = "Cleans up the recognized text, separated by commas",
+        long_help = "Cleans up the recognized text, separated by commas\nOnly used for text and markdown output",
+        hide = cfg!(not(feature = "ocr"))
+    )]
+    pub ocr_cleanup: Vec<OcrCleanup>,
+
+    #[arg(
+        long,
+        action,
+        help = "Converts to grayscale, upscales and thresholds the image before OCR",
+        long_help = "Converts to grayscale, upscales and thresholds the image before OCR\nImproves recognition of small UI text",
+        overrides_with = "no_ocr_preprocess",
+        hide = cfg!(not(feature = "ocr"))
+    )]
+    pub ocr_preprocess: bool,
+
+    #[arg(long, action, hide = true, overrides_with = "ocr_preprocess")]
+    pub no_ocr_preprocess: bool,
+}
+
+fn parse_interval(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(secs) if secs > 0.0 => Ok(secs),
+        _ => Err("interval must be a number of seconds greater than 0".to_string()),
+    }
+}
+
+#[derive(Args, Debug)]
+#[command(group(
+    ArgGroup::new("repeat")
+        .required(false)
+        .multiple(false)
+        .args(["interval", "burst"]),
+))]
+pub struct IntervalArgs {
+    #[arg(
+        long,
+        value_name = "SECS",
+        value_parser = parse_interval,
+        help = "Captures the area repeatedly every SECS seconds",
+        long_help = "Captures the area repeatedly every SECS seconds until stopped with 'focal image --stop'\nFiles are numbered using {n} in the filename template",
+        conflicts_with_all = ["rofi", "scroll", "edit", "ocr", "qr", "decode"]
+    )]
+    pub interval: Option<f64>,
+
+    #[arg(
+        long,
+        action,
+        help = "Captures the area repeatedly as fast as possible [default count: 10]",
+        conflicts_with_all = ["rofi", "scroll", "edit", "ocr", "qr", "decode"]
+    )]
+    pub burst: bool,
+
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u32).range(1..),
+        requires = "repeat",
+        help = "Stops after N captures"
+    )]
+    pub count: Option<u32>,
+
+    #[arg(
+        long,
+        num_args = 0..=1,
+        value_name = "FPS",
+        default_missing_value = "10",
+        value_parser = clap::value_parser!(u32).range(1..=120),
+        requires = "repeat",
+        help = "Assembles the captures into a video with ffmpeg [default: 10 fps]"
+    )]
+    pub timelapse: Option<u32>,
+
+    #[arg(long, action, help = "Stops any running interval captures")]
+    pub stop: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct BeautifyArgs {
+    #[arg(
+        long,
+        action,
+        overrides_with = "no_beautify",
+        help = "Adds padding, a background, rounded corners and a drop shadow to the screenshot"
+    )]
+    pub beautify: bool,
+
+    #[arg(long, action, hide = true, overrides_with = "beautify")]
+    pub no_beautify: bool,
+
+    #[arg(
+        long,
+        value_name = "PIXELS",
+        help = "Padding around the screenshot when beautifying [default: 64]"
+    )]
+    pub padding: Option<u32>,
+
+    #[arg(
+        long,
+        value_name = "COLOR",
+        help = "Background when beautifying [default: #1e1e2e:#585b70]",
+        long_help = "Background when beautifying [default: #1e1e2e:#585b70]\nEither a single color, or two colors separated by : for a gradient"
+    )]
+    pub background: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "PIXELS",
+        help = "Corner radius when beautifying [default: 12]"
+    )]
+    pub radius: Option<u32>,
+
+    #[arg(
+        long,
+        value_name = "PIXELS",
+        help = "Size of the drop shadow when beautifying, 0 to disable [default: 24]"
+    )]
+    pub shadow: Option<u32>,
 }
 
 #[allow(clippy::struct_excessive_bools)]
 #[derive(Args, Debug)]
 #[command(group(
     ArgGroup::new("area_shortcuts")
-        .args(["area", "window", "selection", "monitor", "all"])
+        .args(["area", "window", "selection", "monitor", "all", "each_monitor"])
         .multiple(false)
 ))]
 pub struct AreaArgs {
@@ -56,11 +296,19 @@ pub struct AreaArgs {
     #[arg(
         long,
         group = "area_shortcuts",
-        hide = std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default() == "niri",
         help = "",
         long_help = "Shorthand for --area all"
     )]
     pub all: bool,
+
+    #[arg(
+        long,
+        group = "area_shortcuts",
+        help = "",
+        long_help = "Shorthand for --area each-monitor",
+        conflicts_with_all = ["edit", "ocr", "qr", "decode"]
+    )]
+    pub each_monitor: bool,
 }
 
 impl AreaArgs {
@@ -73,19 +321,21 @@ impl AreaArgs {
             Some(CaptureArea::Monitor)
         } else if self.all {
             Some(CaptureArea::All)
+        } else if self.each_monitor {
+            Some(CaptureArea::EachMonitor)
         } else {
             self.area.clone()
         }
     }
 }
 
-#[allow(clippy::module_name_repetitions)]
+#[allow(clippy::module_name_repetitions, clippy::struct_excessive_bools)]
 #[derive(Args, Debug)]
 #[command(group(
     ArgGroup::new("required_mode")
         .required(true)
         .multiple(false)
-        .args(["rofi", "area", "selection", "window", "monitor", "all"]),
+        .args(["rofi", "area", "selection", "window", "monitor", "all", "each_monitor", "stop"]),
 ))]
 #[command(group(
     ArgGroup::new("freeze_mode")
@@ -101,10 +351,35 @@ pub struct ImageArgs {
         long,
         action,
         help = "Freezes the screen before selecting an area.",
+        overrides_with = "no_freeze",
         hide = std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default() == "niri",
     )]
     pub freeze: bool,
 
+    #[arg(long, action, hide = true, overrides_with = "freeze")]
+    pub no_freeze: bool,
+
+    #[arg(
+        long,
+        action,
+        help = "Captures a long screenshot of the selection while scrolling",
+        long_help = "Captures a long screenshot of the selection while scrolling\nThe selection is captured until it stops changing for a few seconds, then stitched into one image",
+        conflicts_with_all = ["rofi", "freeze"]
+    )]
+    pub scroll: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        value_name = "PROGRAM",
+        requires = "scroll",
+        help = "Scrolls automatically using PROGRAM instead of waiting for the user to scroll"
+    )]
+    pub scroll_with: Option<ScrollInput>,
+
+    #[command(flatten)]
+    pub interval_args: IntervalArgs,
+
     #[command(flatten)]
     pub common_args: CommonArgs,
 
@@ -114,10 +389,13 @@ pub struct ImageArgs {
     #[arg(
         short,
         long,
+        num_args = 0..=1,
+        default_missing_value = "",
         action,
-        help = "Edit screenshot using COMMAND\nThe image path will be passed as $IMAGE",
+        help = "Edit screenshot using COMMAND\nThe image path will be passed as $IMAGE or {}",
+        long_help = "Edit screenshot using COMMAND, defaulting to the editor from the config file\n$IMAGE or {} in COMMAND are replaced with the image path, otherwise it is passed as the last argument\nsatty, swappy, gimp, krita and pinta without arguments save the edited image in place",
         value_name = "COMMAND",
-        conflicts_with = "ocr"
+        conflicts_with_all = ["ocr", "qr", "decode"]
     )]
     pub edit: Option<String>,
 
@@ -128,22 +406,96 @@ pub struct ImageArgs {
         default_missing_value = "",
         action,
         help = "Runs OCR on the selected text",
-        long_help = "Runs OCR on the selected text, defaulting to English\nSupported languages can be shown using 'tesseract --list-langs'",
-        conflicts_with = "edit",
+        long_help = "Runs OCR on the selected text, defaulting to English\nMultiple languages can be given as eng+deu\nSupported languages can be shown using 'tesseract --list-langs'",
+        conflicts_with_all = ["edit", "qr", "decode"],
         hide = cfg!(not(feature = "ocr"))
     )]
     pub ocr: Option<String>,
 
+    #[command(flatten)]
+    pub ocr_args: OcrArgs,
+
+    #[arg(
+        long,
+        action,
+        help = "Decodes a QR code in the screenshot",
+        long_help = "Decodes a QR code in the screenshot and copies its contents\nURLs can be opened from the notification",
+        conflicts_with = "decode"
+    )]
+    pub qr: bool,
+
+    #[arg(
+        long,
+        action,
+        help = "Decodes any barcode supported by zbar in the screenshot",
+        long_help = "Decodes any barcode supported by zbar in the screenshot and copies its contents\nURLs can be opened from the notification"
+    )]
+    pub decode: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "Image format of the screenshot",
+        long_help = "Image format of the screenshot\nDefaults to the extension of FILE if provided, otherwise png"
+    )]
+    pub format: Option<OutputFormat>,
+
+    #[arg(
+        long,
+        value_name = "QUALITY",
+        value_parser = clap::value_parser!(u8).range(1..=100),
+        help = "Quality of the screenshot from 1-100",
+        long_help = "Quality of the screenshot from 1-100\nOnly used for lossy formats (jpeg, avif)"
+    )]
+    pub quality: Option<u8>,
+
+    #[arg(
+        long,
+        value_name = "FACTOR",
+        value_parser = parse_scale,
+        conflicts_with = "logical",
+        help = "Scale factor of the screenshot"
+    )]
+    pub scale: Option<f64>,
+
+    #[arg(
+        long,
+        action,
+        help = "Captures at the logical size of the monitor, ignoring its scale",
+        long_help = "Captures at the logical size of the monitor, ignoring its scale\nUseful for keeping screenshots from HiDPI monitors small"
+    )]
+    pub logical: bool,
+
+    #[arg(
+        long,
+        value_name = "PIXELS",
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Maximum width of the screenshot, downscaling if larger"
+    )]
+    pub max_width: Option<u32>,
+
+    #[arg(
+        long,
+        value_name = "PIXELS",
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Maximum height of the screenshot, downscaling if larger"
+    )]
+    pub max_height: Option<u32>,
+
+    #[command(flatten)]
+    pub beautify_args: BeautifyArgs,
+
     #[arg(
         name = "FILE",
-        help = "Files are created in XDG_PICTURES_DIR/Screenshots if not specified"
+        help = "Files are created in XDG_PICTURES_DIR/Screenshots if not specified, - writes to stdout"
     )]
     pub filename: Option<PathBuf>,
 }
 
 impl ImageArgs {
     pub fn required_programs(&self) -> Vec<&str> {
-        let mut progs = vec!["grim"];
+        // grim is only checked when falling back from capturing natively
+        let mut progs = vec![];
 
         if self.rofi_args.rofi {
             progs.push("rofi");
@@ -158,6 +510,18 @@ impl ImageArgs {
             progs.push("tesseract");
         }
 
+        if self.qr || self.decode {
+            progs.push("zbarimg");
+        }
+
+        if self.interval_args.timelapse.is_some() {
+            progs.push("ffmpeg");
+        }
+
+        if let Some(input) = self.scroll_with {
+            progs.push(input.program());
+        }
+
         progs
     }
 }
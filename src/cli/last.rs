@@ -0,0 +1,63 @@
+use clap::{Args, ValueEnum};
+
+use crate::cli::history::HistoryKind;
+
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LastAction {
+    /// copies the capture to the clipboard
+    Copy,
+    /// copies the path of the capture to the clipboard
+    CopyPath,
+    /// opens the capture with xdg-open
+    Open,
+    /// edits the screenshot
+    Edit,
+    /// copies the text in the screenshot
+    Ocr,
+    /// deletes the capture
+    Delete,
+    /// prints the path of the capture
+    #[default]
+    Path,
+}
+
+#[derive(Args, Debug)]
+pub struct LastArgs {
+    #[arg(value_enum, default_value_t, help = "Action to perform on the capture")]
+    pub action: LastAction,
+
+    #[arg(long, value_enum, help = "Use the most recent image or video")]
+    pub kind: Option<HistoryKind>,
+
+    #[arg(
+        long,
+        value_name = "COMMAND",
+        help = "Editor used for edit, defaulting to the editor from the config file"
+    )]
+    pub editor: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "LANG",
+        help = "Language used for ocr, defaulting to the language from the config file"
+    )]
+    pub ocr_lang: Option<String>,
+
+    #[arg(
+        long,
+        action,
+        overrides_with = "notify",
+        help = "Do not show notifications"
+    )]
+    pub no_notify: bool,
+
+    #[arg(long, action, hide = true, overrides_with = "no_notify")]
+    pub notify: bool,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Use a named profile from the config file"
+    )]
+    pub profile: Option<String>,
+}
@@ -1,4 +1,9 @@
+pub mod color;
 pub mod focal;
+pub mod history;
 pub mod image;
+pub mod last;
+pub mod process;
+pub mod recent;
 pub mod video;
 pub mod waybar;
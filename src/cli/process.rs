@@ -0,0 +1,184 @@
+use std::path::PathBuf;
+
+use crate::cli::{
+    focal::CopyMode,
+    image::{BeautifyArgs, OcrArgs, OutputFormat},
+};
+use clap::{ArgGroup, Args};
+
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Args, Debug)]
+#[command(group(
+    ArgGroup::new("input")
+        .required(true)
+        .multiple(false)
+        .args(["FILE", "clipboard"]),
+))]
+pub struct ProcessArgs {
+    #[arg(name = "FILE", help = "Image to process")]
+    pub file: Option<PathBuf>,
+
+    #[arg(long, action, help = "Process the image in the clipboard")]
+    pub clipboard: bool,
+
+    #[arg(
+        short,
+        long,
+        value_name = "FILE",
+        help = "Path of the processed image",
+        long_help = "Path of the processed image\nFiles are created in XDG_PICTURES_DIR/Screenshots if not specified"
+    )]
+    pub output: Option<PathBuf>,
+
+    #[arg(
+        short,
+        long,
+        num_args = 0..=1,
+        default_missing_value = "",
+        action,
+        help = "Edit the image using COMMAND\nThe image path will be passed as $IMAGE or {}",
+        value_name = "COMMAND",
+        conflicts_with_all = ["ocr", "qr", "decode"]
+    )]
+    pub edit: Option<String>,
+
+    #[arg(
+        long,
+        num_args = 0..=1,
+        value_name = "LANG",
+        default_missing_value = "",
+        action,
+        help = "Runs OCR on the image",
+        conflicts_with_all = ["edit", "qr", "decode"],
+        hide = cfg!(not(feature = "ocr"))
+    )]
+    pub ocr: Option<String>,
+
+    #[command(flatten)]
+    pub ocr_args: OcrArgs,
+
+    #[arg(
+        long,
+        action,
+        help = "Decodes a QR code in the image",
+        conflicts_with = "decode"
+    )]
+    pub qr: bool,
+
+    #[arg(
+        long,
+        action,
+        help = "Decodes any barcode supported by zbar in the image"
+    )]
+    pub decode: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "Image format of the processed image",
+        long_help = "Image format of the processed image\nDefaults to the extension of --output if provided, otherwise png"
+    )]
+    pub format: Option<OutputFormat>,
+
+    #[arg(
+        long,
+        value_name = "QUALITY",
+        value_parser = clap::value_parser!(u8).range(1..=100),
+        help = "Quality of the image from 1-100"
+    )]
+    pub quality: Option<u8>,
+
+    #[arg(
+        long,
+        value_name = "PIXELS",
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Maximum width of the image, downscaling if larger"
+    )]
+    pub max_width: Option<u32>,
+
+    #[arg(
+        long,
+        value_name = "PIXELS",
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Maximum height of the image, downscaling if larger"
+    )]
+    pub max_height: Option<u32>,
+
+    #[command(flatten)]
+    pub beautify_args: BeautifyArgs,
+
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        value_name = "MODE",
+        help = "What to copy to the clipboard, can be given multiple times"
+    )]
+    pub copy: Vec<CopyMode>,
+
+    #[arg(
+        long,
+        action,
+        overrides_with = "no_primary",
+        help = "Also copy to the primary selection"
+    )]
+    pub primary: bool,
+
+    #[arg(long, action, hide = true, overrides_with = "primary")]
+    pub no_primary: bool,
+
+    #[arg(long, value_name = "NAME", help = "Upload the image and copy its url")]
+    pub upload: Option<String>,
+
+    #[arg(
+        long,
+        action,
+        overrides_with = "notify",
+        help = "Do not show notifications"
+    )]
+    pub no_notify: bool,
+
+    #[arg(long, action, hide = true, overrides_with = "no_notify")]
+    pub notify: bool,
+
+    #[arg(
+        long,
+        action,
+        overrides_with = "save",
+        help = "Do not save the file permanently"
+    )]
+    pub no_save: bool,
+
+    #[arg(long, action, hide = true, overrides_with = "no_save")]
+    pub save: bool,
+
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        help = "Template for generated filenames"
+    )]
+    pub template: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Use a named profile from the config file"
+    )]
+    pub profile: Option<String>,
+}
+
+impl ProcessArgs {
+    pub fn required_programs(&self) -> Vec<&str> {
+        let mut progs = Vec::new();
+
+        if self.ocr.is_some() {
+            progs.push("tesseract");
+        }
+
+        if self.qr || self.decode {
+            progs.push("zbarimg");
+        }
+
+        progs
+    }
+}
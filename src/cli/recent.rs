@@ -0,0 +1,44 @@
+use clap::{ArgGroup, Args};
+
+use crate::cli::{focal::RofiArgs, history::HistoryKind};
+
+#[derive(Args, Debug)]
+#[command(group(
+    ArgGroup::new("mode")
+        .required(true)
+        .args(["rofi"]),
+))]
+pub struct RecentArgs {
+    #[command(flatten)]
+    pub rofi_args: RofiArgs,
+
+    #[arg(
+        short = 'n',
+        long,
+        value_name = "N",
+        default_value_t = 10,
+        help = "Show the N most recent captures"
+    )]
+    pub limit: usize,
+
+    #[arg(long, value_enum, help = "Only show images or videos")]
+    pub kind: Option<HistoryKind>,
+
+    #[arg(
+        long,
+        action,
+        overrides_with = "notify",
+        help = "Do not show notifications"
+    )]
+    pub no_notify: bool,
+
+    #[arg(long, action, hide = true, overrides_with = "no_notify")]
+    pub notify: bool,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Use a named profile from the config file"
+    )]
+    pub profile: Option<String>,
+}
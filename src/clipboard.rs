@@ -0,0 +1,310 @@
+use std::{
+    io::Write,
+    os::unix::{ffi::OsStrExt, process::CommandExt},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use serde::{Deserialize, Serialize};
+use wl_clipboard_rs::copy::{ClipboardType, MimeSource, MimeType, Options, Source};
+
+use crate::cli::focal::CopyMode;
+
+/// mime types offered for text, matching what wl-copy offers
+const TEXT_MIME_TYPES: [&str; 5] = [
+    "text/plain;charset=utf-8",
+    "text/plain",
+    "UTF8_STRING",
+    "STRING",
+    "TEXT",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum Offer {
+    Text { mime: String, text: String },
+    File { mime: String, path: PathBuf },
+}
+
+impl Offer {
+    fn mime(&self) -> &str {
+        match self {
+            Self::Text { mime, .. } | Self::File { mime, .. } => mime,
+        }
+    }
+
+    fn into_source(self) -> std::io::Result<MimeSource> {
+        let (mime, bytes) = match self {
+            Self::Text { mime, text } => (mime, text.into_bytes()),
+            Self::File { mime, path } => (mime, std::fs::read(path)?),
+        };
+
+        Ok(MimeSource {
+            source: Source::Bytes(bytes.into_boxed_slice()),
+            mime_type: MimeType::Specific(mime),
+        })
+    }
+}
+
+/// percent-encodes everything but unreserved characters and the / between path segments
+fn percent_encode(path: &Path) -> String {
+    path.as_os_str()
+        .as_bytes()
+        .iter()
+        .map(|&b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                char::from(b).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+/// file:// uri for a path
+pub fn file_uri(path: &Path) -> String {
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    format!("file://{}", percent_encode(&path))
+}
+
+/// markdown link to the file, images are embedded
+fn markdown(path: &Path, mime: &str) -> String {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let name = name
+        .replace('\\', "\\\\")
+        .replace('[', "\\[")
+        .replace(']', "\\]");
+
+    let prefix = if mime.starts_with("image/") { "!" } else { "" };
+    format!("{prefix}[{name}]({})", percent_encode(path))
+}
+
+/// contents to be copied to the clipboard, each offered as a different mime type
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Clipboard {
+    offers: Vec<Offer>,
+    primary: bool,
+}
+
+impl Clipboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// also copies to the primary selection
+    #[must_use]
+    pub const fn primary(mut self, primary: bool) -> Self {
+        self.primary = primary;
+        self
+    }
+
+    fn offer(mut self, offer: Offer) -> Self {
+        // the first offer for a mime type wins
+        if !self.offers.iter().any(|o| o.mime() == offer.mime()) {
+            self.offers.push(offer);
+        }
+        self
+    }
+
+    #[must_use]
+    pub fn text(self, text: &str) -> Self {
+        TEXT_MIME_TYPES.iter().fold(self, |clipboard, mime| {
+            clipboard.offer(Offer::Text {
+                mime: (*mime).to_string(),
+                text: text.to_string(),
+            })
+        })
+    }
+
+    #[must_use]
+    pub fn uri_list(self, path: &Path) -> Self {
+        self.offer(Offer::Text {
+            mime: "text/uri-list".to_string(),
+            text: file_uri(path),
+        })
+    }
+
+    #[must_use]
+    pub fn file(self, mime: &str, path: &Path) -> Self {
+        self.offer(Offer::File {
+            mime: mime.to_string(),
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// adds the offers for the given copy modes, mime is the type of the file contents
+    #[must_use]
+    pub fn modes(self, modes: &[CopyMode], path: &Path, mime: &str) -> Self {
+        modes.iter().fold(self, |clipboard, mode| match mode {
+            CopyMode::Image => clipboard.file(mime, path).uri_list(path),
+            CopyMode::Path => clipboard.text(&path.to_string_lossy()),
+            CopyMode::Uri => clipboard.uri_list(path),
+            CopyMode::Markdown => clipboard.text(&markdown(path, mime)),
+            CopyMode::None => clipboard,
+        })
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.offers.is_empty()
+    }
+
+    /// copies to the clipboard by serving it from a background process
+    pub fn copy(&self) {
+        if self.is_empty() {
+            return;
+        }
+
+        // the process is not waited on, it exits once the clipboard is replaced
+        #[allow(clippy::zombie_processes)]
+        let mut child = Command::new(std::env::current_exe().expect("could not get focal path"))
+            .arg("clipboard-serve")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            // keep serving after focal exits
+            .process_group(0)
+            .spawn()
+            .expect("failed to spawn clipboard process");
+
+        let content = serde_json::to_string(self).expect("failed to serialize clipboard");
+        child
+            .stdin
+            .take()
+            .expect("failed to open clipboard process stdin")
+            .write_all(content.as_bytes())
+            .expect("failed to write to clipboard process");
+    }
+
+    /// the contents of each offer, reading the files to copy
+    fn sources(self) -> std::io::Result<Vec<MimeSource>> {
+        self.offers.into_iter().map(Offer::into_source).collect()
+    }
+
+    /// serves the clipboard read from stdin until it is replaced
+    pub fn serve() {
+        let clipboard: Self = serde_json::from_reader(std::io::stdin().lock())
+            .expect("failed to read clipboard contents");
+        let primary = clipboard.primary;
+
+        let sources = clipboard.sources().expect("failed to read file to copy");
+
+        let mut opts = Options::new();
+        opts.foreground(true).clipboard(if primary {
+            ClipboardType::Both
+        } else {
+            ClipboardType::Regular
+        });
+
+        opts.copy_multi(sources)
+            .expect("failed to copy to clipboard");
+    }
+}
+
+/// returns the image in the clipboard, preferring png
+pub fn paste_image() -> Option<Vec<u8>> {
+    use std::io::Read;
+    use wl_clipboard_rs::paste::{self, Seat};
+
+    let mimes = paste::get_mime_types(paste::ClipboardType::Regular, Seat::Unspecified).ok()?;
+    let mime = if mimes.contains("image/png") {
+        "image/png"
+    } else {
+        mimes.iter().find(|mime| mime.starts_with("image/"))?
+    };
+
+    let (mut pipe, _) = paste::get_contents(
+        paste::ClipboardType::Regular,
+        Seat::Unspecified,
+        paste::MimeType::Specific(mime),
+    )
+    .ok()?;
+
+    let mut contents = Vec::new();
+    pipe.read_to_end(&mut contents).ok()?;
+    Some(contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mimes(clipboard: &Clipboard) -> Vec<&str> {
+        clipboard.offers.iter().map(Offer::mime).collect()
+    }
+
+    #[test]
+    fn test_modes() {
+        let path = Path::new("/tmp/a.png");
+
+        let image = Clipboard::new().modes(&[CopyMode::Image], path, "image/png");
+        assert_eq!(mimes(&image), ["image/png", "text/uri-list"]);
+
+        let clipboard = Clipboard::new().modes(
+            &[CopyMode::Path, CopyMode::Markdown, CopyMode::Uri],
+            path,
+            "image/png",
+        );
+        assert_eq!(mimes(&clipboard).len(), TEXT_MIME_TYPES.len() + 1);
+        assert!(
+            matches!(&clipboard.offers[0], Offer::Text { text, .. } if text == "/tmp/a.png"),
+            "the first text mode should be used"
+        );
+
+        assert!(Clipboard::new().modes(&[], path, "image/png").is_empty());
+    }
+
+    #[test]
+    fn test_escaping() {
+        let path = Path::new("/tmp/GitHub | Firefox [1] (#2) 100%é.png");
+
+        assert_eq!(
+            file_uri(path),
+            "file:///tmp/GitHub%20%7C%20Firefox%20%5B1%5D%20%28%232%29%20100%25%C3%A9.png"
+        );
+        assert_eq!(
+            markdown(path, "image/png"),
+            "![GitHub | Firefox \\[1\\] (#2) 100%é.png](/tmp/GitHub%20%7C%20Firefox%20%5B1%5D%20%28%232%29%20100%25%C3%A9.png)"
+        );
+        assert_eq!(
+            markdown(Path::new("/tmp/a.mp4"), "video/mp4"),
+            "[a.mp4](/tmp/a.mp4)"
+        );
+    }
+
+    #[test]
+    fn test_serve() {
+        let path = std::env::temp_dir().join(format!("focal-clipboard-{}.png", std::process::id()));
+        std::fs::write(&path, b"png").expect("failed to write test file");
+
+        // the clipboard is passed to the background process as json
+        let clipboard = Clipboard::new().primary(true).modes(
+            &[CopyMode::Image, CopyMode::Path],
+            &path,
+            "image/png",
+        );
+        let json = serde_json::to_string(&clipboard).expect("failed to serialize clipboard");
+        let served: Clipboard = serde_json::from_str(&json).expect("failed to read clipboard");
+        assert!(served.primary);
+
+        let sources = served.sources().expect("failed to read sources");
+        std::fs::remove_file(&path).expect("failed to remove test file");
+
+        let contents: Vec<_> = sources
+            .iter()
+            .map(|source| {
+                let MimeType::Specific(mime) = &source.mime_type else {
+                    panic!("mime types should be specific");
+                };
+                let Source::Bytes(bytes) = &source.source else {
+                    panic!("sources should be bytes");
+                };
+                (mime.as_str(), bytes.to_vec())
+            })
+            .collect();
+
+        assert_eq!(contents[0], ("image/png", b"png".to_vec()));
+        assert_eq!(contents[1], ("text/uri-list", file_uri(&path).into_bytes()));
+        assert_eq!(contents.len(), 2 + TEXT_MIME_TYPES.len());
+    }
+}
@@ -0,0 +1,267 @@
+use std::{
+    fmt,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+use execute::Execute;
+
+use crate::{
+    check_programs,
+    cli::color::{ColorArgs, ColorFormat},
+    clipboard::Clipboard,
+    config::Config,
+    freeze::Freezer,
+    image::Grim,
+    is_hyprland, is_niri,
+};
+
+#[derive(Debug)]
+pub struct ColorError {
+    message: String,
+}
+
+impl fmt::Display for ColorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl std::str::FromStr for Color {
+    type Err = ColorError;
+
+    /// parses a hex color, e.g. #ff8000
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.trim().trim_start_matches('#');
+        let err = || ColorError {
+            message: format!("Invalid hex color: {s}"),
+        };
+
+        if hex.len() != 6 {
+            return Err(err());
+        }
+
+        let channel = |idx: usize| {
+            hex.get(idx..idx + 2)
+                .and_then(|c| u8::from_str_radix(c, 16).ok())
+                .ok_or_else(err)
+        };
+
+        Ok(Self {
+            r: channel(0)?,
+            g: channel(2)?,
+            b: channel(4)?,
+        })
+    }
+}
+
+impl Color {
+    /// hue in degrees, saturation and lightness as percentages
+    #[allow(clippy::many_single_char_names)]
+    fn hsl(self) -> (f64, f64, f64) {
+        let [r, g, b] = [self.r, self.g, self.b].map(|c| f64::from(c) / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let l = f64::midpoint(max, min);
+
+        let delta = max - min;
+        if delta == 0.0 {
+            return (0.0, 0.0, l * 100.0);
+        }
+
+        let s = delta / (1.0 - 2.0f64.mul_add(l, -1.0).abs());
+        let h = if (max - r).abs() < f64::EPSILON {
+            ((g - b) / delta).rem_euclid(6.0)
+        } else if (max - g).abs() < f64::EPSILON {
+            (b - r) / delta + 2.0
+        } else {
+            (r - g) / delta + 4.0
+        };
+
+        (h * 60.0, s * 100.0, l * 100.0)
+    }
+
+    pub fn format(self, format: ColorFormat) -> String {
+        let Self { r, g, b } = self;
+
+        match format {
+            ColorFormat::Hex => format!("#{r:02x}{g:02x}{b:02x}"),
+            ColorFormat::Rgb => format!("rgb({r}, {g}, {b})"),
+            ColorFormat::Hsl => {
+                let (h, s, l) = self.hsl();
+                format!("hsl({h:.0}, {s:.0}%, {l:.0}%)")
+            }
+        }
+    }
+}
+
+/// picks a color using niri's color picker
+fn niri_pick() -> Option<Color> {
+    use niri_ipc::{Request, Response, socket::Socket};
+
+    let Ok(Response::PickedColor(picked)) = Socket::connect()
+        .expect("failed to connect to niri socket")
+        .send(Request::PickColor)
+        .expect("failed to send PickColor request to niri")
+    else {
+        panic!("unexpected response from niri, should be PickedColor");
+    };
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    picked.map(|picked| {
+        let [r, g, b] = picked
+            .rgb
+            .map(|c| (c * 255.0).round().clamp(0.0, 255.0) as u8);
+        Color { r, g, b }
+    })
+}
+
+/// picks a color using hyprpicker, which freezes the screen and shows a zoom lens
+fn hyprpicker_pick() -> Option<Color> {
+    let output = Command::new("hyprpicker")
+        .arg("--format=hex")
+        .arg("--no-fancy")
+        .stdout(Stdio::piped())
+        .execute_output()
+        .expect("failed to execute hyprpicker");
+
+    // hyprpicker prints nothing when cancelled
+    String::from_utf8_lossy(&output.stdout).parse().ok()
+}
+
+/// picks a point with slurp and reads the pixel from a grim capture
+fn slurp_pick(freeze: bool) -> Option<Color> {
+    // the zoom lens would be captured instead of the pixel
+    let freezer = freeze.then(|| Freezer::new(false));
+
+    let output = Command::new("slurp")
+        .arg("-p")
+        .stdout(Stdio::piped())
+        .execute_output()
+        .expect("failed to execute slurp");
+
+    let point = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if point.is_empty() {
+        return None;
+    }
+
+    // the frozen screen is captured, so only stop freezing afterwards
+    let png = Grim::new(PathBuf::from("-"))
+        .geometry(&point)
+        .try_capture_bytes();
+    // exiting skips the drop, so the freezer is stopped before reporting errors
+    drop(freezer);
+
+    let Some(png) = png else {
+        eprintln!("No image was captured by grim!");
+        std::process::exit(1);
+    };
+
+    let img = image::load_from_memory(&png)
+        .expect("failed to read grim capture")
+        .to_rgb8();
+    let image::Rgb([r, g, b]) = *img.get_pixel(0, 0);
+
+    Some(Color { r, g, b })
+}
+
+/// shows a notification with a swatch of the color
+fn notify(color: Color, text: &str) {
+    let swatch = dirs::runtime_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("focal-color.png");
+
+    image::RgbImage::from_pixel(64, 64, image::Rgb([color.r, color.g, color.b]))
+        .save(&swatch)
+        .expect("failed to create color swatch");
+
+    notify_rust::Notification::new()
+        .appname("focal")
+        .body(&format!("Color copied: {text}"))
+        .icon(&swatch.to_string_lossy())
+        .timeout(3000)
+        .show()
+        .expect("Failed to send notification");
+}
+
+pub fn main(mut args: ColorArgs) {
+    let config = Config::load(args.profile.as_deref());
+    config.apply_color(&mut args);
+
+    let native = !args.no_native;
+    let color = if native && is_niri() {
+        niri_pick()
+    } else if native && is_hyprland() && which::which("hyprpicker").is_ok() {
+        hyprpicker_pick()
+    } else {
+        check_programs(&["slurp", "grim"]);
+        slurp_pick(args.freeze)
+    };
+
+    let Some(color) = color else {
+        eprintln!("No color was picked.");
+        std::process::exit(1);
+    };
+
+    let text = color.format(args.format);
+    println!("{text}");
+
+    Clipboard::new().primary(args.primary).text(&text).copy();
+
+    if !args.no_notify {
+        notify(color, &text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            "#FF8000\n".parse::<Color>().expect("valid hex color"),
+            Color {
+                r: 255,
+                g: 128,
+                b: 0
+            }
+        );
+        assert!("".parse::<Color>().is_err());
+        assert!("#ff80".parse::<Color>().is_err());
+        assert!("#gg8000".parse::<Color>().is_err());
+    }
+
+    #[test]
+    fn test_format() {
+        let color = Color {
+            r: 255,
+            g: 128,
+            b: 0,
+        };
+        assert_eq!(color.format(ColorFormat::Hex), "#ff8000");
+        assert_eq!(color.format(ColorFormat::Rgb), "rgb(255, 128, 0)");
+        assert_eq!(color.format(ColorFormat::Hsl), "hsl(30, 100%, 50%)");
+
+        let gray = Color {
+            r: 128,
+            g: 128,
+            b: 128,
+        };
+        assert_eq!(gray.format(ColorFormat::Hsl), "hsl(0, 0%, 50%)");
+
+        let teal = Color {
+            r: 32,
+            g: 96,
+            b: 160,
+        };
+        assert_eq!(teal.format(ColorFormat::Hsl), "hsl(210, 67%, 38%)");
+    }
+}
@@ -0,0 +1,603 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+use serde_derive::Deserialize;
+
+use crate::{
+    cli::{
+        color::ColorArgs,
+        focal::{CommonArgs, CopyMode, RofiArgs},
+        image::{BeautifyArgs, ImageArgs, OcrArgs, OcrCleanup, OutputFormat},
+        process::ProcessArgs,
+        recent::RecentArgs,
+        video::VideoArgs,
+    },
+    hooks::Hook,
+    ocr::Ocr,
+    upload::Upload,
+};
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct BeautifyConfig {
+    pub enabled: Option<bool>,
+    pub padding: Option<u32>,
+    pub background: Option<String>,
+    pub radius: Option<u32>,
+    pub shadow: Option<u32>,
+}
+
+impl BeautifyConfig {
+    /// returns the config with values from other taking precedence
+    fn with(&self, other: Self) -> Self {
+        Self {
+            enabled: other.enabled.or(self.enabled),
+            padding: other.padding.or(self.padding),
+            background: other.background.or_else(|| self.background.clone()),
+            radius: other.radius.or(self.radius),
+            shadow: other.shadow.or(self.shadow),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ImageConfig {
+    /// directory screenshots are saved to
+    pub dir: Option<PathBuf>,
+    pub freeze: Option<bool>,
+    /// editor used for --edit without a command and for the rofi menu
+    pub editor: Option<String>,
+    /// language used for --ocr without a language
+    pub ocr_lang: Option<String>,
+    pub ocr_psm: Option<u8>,
+    pub ocr_format: Option<String>,
+    pub ocr_cleanup: Option<Vec<String>>,
+    pub ocr_preprocess: Option<bool>,
+    pub format: Option<String>,
+    pub quality: Option<u8>,
+    pub scale: Option<f64>,
+    pub logical: Option<bool>,
+    pub max_width: Option<u32>,
+    pub max_height: Option<u32>,
+    pub cursor: Option<bool>,
+    /// what is copied to the clipboard, defaults to image
+    pub copy: Option<Vec<String>>,
+    pub beautify: BeautifyConfig,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct VideoConfig {
+    /// directory videos are saved to
+    pub dir: Option<PathBuf>,
+    /// device used for --audio without a device
+    pub audio_device: Option<String>,
+    pub duration: Option<u64>,
+    /// what is copied to the clipboard, defaults to uri
+    pub copy: Option<Vec<String>>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    pub delay: Option<u64>,
+    pub slurp: Option<String>,
+    pub no_rounded_windows: Option<bool>,
+    pub no_notify: Option<bool>,
+    pub no_save: Option<bool>,
+    pub no_icons: Option<bool>,
+    pub theme: Option<PathBuf>,
+    pub template: Option<String>,
+    pub primary: Option<bool>,
+    pub image: ImageConfig,
+    pub video: VideoConfig,
+    /// commands run after capturing, after any hooks given on the command line
+    pub hooks: Vec<Hook>,
+    /// endpoints used by --upload
+    pub uploads: HashMap<String, Upload>,
+    pub profiles: HashMap<String, Self>,
+}
+
+/// parses a value from the config file, exiting if it is invalid
+fn parse_value<T: ValueEnum>(name: &str, value: &str) -> T {
+    T::from_str(value, true).unwrap_or_else(|_| {
+        eprintln!("Invalid {name} in config file: {value}");
+        std::process::exit(1);
+    })
+}
+
+/// checks a maximum dimension from the config file, exiting if it is 0
+fn max_dimension(name: &str, value: Option<u32>) -> Option<u32> {
+    if value == Some(0) {
+        eprintln!("Invalid {name} in config file: must be at least 1");
+        std::process::exit(1);
+    }
+    value
+}
+
+/// parses the copy modes from the config file, exiting if any are invalid
+fn parse_copy_modes(modes: &[String]) -> Vec<CopyMode> {
+    modes
+        .iter()
+        .map(|mode| parse_value("copy mode", mode))
+        .collect()
+}
+
+/// expands a leading ~ to the home directory
+fn expand_home(path: &Path) -> PathBuf {
+    path.strip_prefix("~").map_or_else(
+        |_| path.to_path_buf(),
+        |rest| dirs::home_dir().expect("could not get $HOME").join(rest),
+    )
+}
+
+/// uses the config value for the flag, unless the flag or its negation was given
+fn apply_flag(flag: &mut bool, negated: bool, config: Option<bool>) {
+    if !*flag && !negated {
+        *flag = config.unwrap_or_default();
+    }
+}
+
+impl Config {
+    pub fn path() -> PathBuf {
+        dirs::config_dir()
+            .expect("could not get $XDG_CONFIG_HOME")
+            .join("focal/config.toml")
+    }
+
+    /// reads the config file, applying the given profile on top of it
+    pub fn load(profile: Option<&str>) -> Self {
+        let path = Self::path();
+
+        let config = if path.exists() {
+            let content = std::fs::read_to_string(&path).expect("failed to read config file");
+            Self::parse(&content).unwrap_or_else(|err| {
+                eprintln!("Invalid config file {}: {err}", path.display());
+                std::process::exit(1);
+            })
+        } else {
+            Self::default()
+        };
+
+        match profile {
+            None => config,
+            Some(name) => config.with_profile(name).unwrap_or_else(|| {
+                eprintln!("Profile {name} not found in {}", path.display());
+                std::process::exit(1);
+            }),
+        }
+    }
+
+    pub fn parse(content: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(content)
+    }
+
+    /// returns the config with the values of the named profile taking precedence
+    pub fn with_profile(&self, name: &str) -> Option<Self> {
+        let profile = self.profiles.get(name)?.clone();
+
+        Some(Self {
+            delay: profile.delay.or(self.delay),
+            slurp: profile.slurp.or_else(|| self.slurp.clone()),
+            no_rounded_windows: profile.no_rounded_windows.or(self.no_rounded_windows),
+            no_notify: profile.no_notify.or(self.no_notify),
+            no_save: profile.no_save.or(self.no_save),
+            no_icons: profile.no_icons.or(self.no_icons),
+            theme: profile.theme.or_else(|| self.theme.clone()),
+            template: profile.template.or_else(|| self.template.clone()),
+            primary: profile.primary.or(self.primary),
+            image: ImageConfig {
+                dir: profile.image.dir.or_else(|| self.image.dir.clone()),
+                freeze: profile.image.freeze.or(self.image.freeze),
+                editor: profile.image.editor.or_else(|| self.image.editor.clone()),
+                ocr_lang: profile
+                    .image
+                    .ocr_lang
+                    .or_else(|| self.image.ocr_lang.clone()),
+                ocr_psm: profile.image.ocr_psm.or(self.image.ocr_psm),
+                ocr_format: profile
+                    .image
+                    .ocr_format
+                    .or_else(|| self.image.ocr_format.clone()),
+                ocr_cleanup: profile
+                    .image
+                    .ocr_cleanup
+                    .or_else(|| self.image.ocr_cleanup.clone()),
+                ocr_preprocess: profile.image.ocr_preprocess.or(self.image.ocr_preprocess),
+                format: profile.image.format.or_else(|| self.image.format.clone()),
+                quality: profile.image.quality.or(self.image.quality),
+                scale: profile.image.scale.or(self.image.scale),
+                logical: profile.image.logical.or(self.image.logical),
+                max_width: profile.image.max_width.or(self.image.max_width),
+                max_height: profile.image.max_height.or(self.image.max_height),
+                cursor: profile.image.cursor.or(self.image.cursor),
+                copy: profile.image.copy.or_else(|| self.image.copy.clone()),
+                beautify: self.image.beautify.with(profile.image.beautify),
+            },
+            video: VideoConfig {
+                dir: profile.video.dir.or_else(|| self.video.dir.clone()),
+                audio_device: profile
+                    .video
+                    .audio_device
+                    .or_else(|| self.video.audio_device.clone()),
+                duration: profile.video.duration.or(self.video.duration),
+                copy: profile.video.copy.or_else(|| self.video.copy.clone()),
+            },
+            // hooks in the profile are run in addition to the global hooks
+            hooks: self.hooks.iter().chain(&profile.hooks).cloned().collect(),
+            uploads: self
+                .uploads
+                .clone()
+                .into_iter()
+                .chain(profile.uploads)
+                .collect(),
+            profiles: HashMap::new(),
+        })
+    }
+
+    /// directory for screenshots, defaulting to `$XDG_PICTURES_DIR/Screenshots`
+    pub fn image_dir(&self) -> PathBuf {
+        self.image.dir.as_deref().map_or_else(
+            || {
+                dirs::picture_dir()
+                    .expect("could not get $XDG_PICTURES_DIR")
+                    .join("Screenshots")
+            },
+            expand_home,
+        )
+    }
+
+    /// directory for videos, defaulting to `$XDG_VIDEOS_DIR/Screencasts`
+    pub fn video_dir(&self) -> PathBuf {
+        self.video.dir.as_deref().map_or_else(
+            || {
+                dirs::video_dir()
+                    .expect("could not get $XDG_VIDEOS_DIR")
+                    .join("Screencasts")
+            },
+            expand_home,
+        )
+    }
+
+    /// returns the endpoint for --upload, exiting if it does not exist
+    pub fn upload(&self, name: Option<&str>) -> Option<Upload> {
+        let name = name?;
+
+        Some(self.uploads.get(name).cloned().unwrap_or_else(|| {
+            eprintln!("Upload {name} not found in {}", Self::path().display());
+            std::process::exit(1);
+        }))
+    }
+
+    /// fills in options not provided on the command line
+    fn apply_common(&self, args: &mut CommonArgs) {
+        args.delay = args.delay.or(self.delay);
+        args.slurp = args.slurp.take().or_else(|| self.slurp.clone());
+        apply_flag(
+            &mut args.no_rounded_windows,
+            args.rounded_windows,
+            self.no_rounded_windows,
+        );
+        apply_flag(&mut args.no_notify, args.notify, self.no_notify);
+        apply_flag(&mut args.no_save, args.save, self.no_save);
+        args.template = args.template.take().or_else(|| self.template.clone());
+        apply_flag(&mut args.primary, args.no_primary, self.primary);
+    }
+
+    fn apply_rofi(&self, args: &mut RofiArgs) {
+        apply_flag(&mut args.no_icons, args.icons, self.no_icons);
+        args.theme = args
+            .theme
+            .take()
+            .or_else(|| self.theme.as_deref().map(expand_home));
+    }
+
+    pub fn apply_color(&self, args: &mut ColorArgs) {
+        apply_flag(&mut args.freeze, args.no_freeze, self.image.freeze);
+        apply_flag(&mut args.no_notify, args.notify, self.no_notify);
+        apply_flag(&mut args.primary, args.no_primary, self.primary);
+    }
+
+    pub fn apply_recent(&self, args: &mut RecentArgs) {
+        self.apply_rofi(&mut args.rofi_args);
+        apply_flag(&mut args.no_notify, args.notify, self.no_notify);
+    }
+
+    pub fn apply_process(&self, args: &mut ProcessArgs) {
+        apply_flag(&mut args.no_notify, args.notify, self.no_notify);
+        apply_flag(&mut args.no_save, args.save, self.no_save);
+        args.template = args.template.take().or_else(|| self.template.clone());
+        apply_flag(&mut args.primary, args.no_primary, self.primary);
+
+        if args.copy.is_empty()
+            && let Some(copy) = &self.image.copy
+        {
+            args.copy = parse_copy_modes(copy);
+        }
+
+        // the extension of --output takes precedence over the config
+        args.format = args
+            .format
+            .take()
+            .or_else(|| args.output.as_deref().and_then(OutputFormat::from_path))
+            .or_else(|| {
+                self.image
+                    .format
+                    .as_deref()
+                    .map(|format| parse_value("image format", format))
+            });
+        args.quality = args.quality.or(self.image.quality);
+        args.max_width = args
+            .max_width
+            .or_else(|| max_dimension("max-width", self.image.max_width));
+        args.max_height = args
+            .max_height
+            .or_else(|| max_dimension("max-height", self.image.max_height));
+
+        self.apply_beautify(&mut args.beautify_args);
+
+        if let Some(editor) = &self.image.editor
+            && args.edit.as_ref().is_some_and(String::is_empty)
+        {
+            args.edit = Some(editor.clone());
+        }
+
+        self.apply_ocr(&mut args.ocr, &mut args.ocr_args);
+    }
+
+    pub fn apply_image(&self, args: &mut ImageArgs) {
+        self.apply_common(&mut args.common_args);
+        self.apply_rofi(&mut args.rofi_args);
+
+        apply_flag(&mut args.freeze, args.no_freeze, self.image.freeze);
+
+        if args.common_args.copy.is_empty()
+            && let Some(copy) = &self.image.copy
+        {
+            args.common_args.copy = parse_copy_modes(copy);
+        }
+
+        if let Some(cursor) = self.image.cursor
+            && !args.common_args.cursor
+            && !args.common_args.no_cursor
+        {
+            args.common_args.cursor = cursor;
+            args.common_args.no_cursor = !cursor;
+        }
+
+        // the extension of FILE takes precedence over the config
+        args.format = args
+            .format
+            .take()
+            .or_else(|| args.filename.as_deref().and_then(OutputFormat::from_path))
+            .or_else(|| {
+                self.image
+                    .format
+                    .as_deref()
+                    .map(|format| parse_value("image format", format))
+            });
+        args.quality = args.quality.or(self.image.quality);
+        args.max_width = args
+            .max_width
+            .or_else(|| max_dimension("max-width", self.image.max_width));
+        args.max_height = args
+            .max_height
+            .or_else(|| max_dimension("max-height", self.image.max_height));
+
+        // --scale and --logical are exclusive
+        if args.scale.is_none() && !args.logical {
+            args.scale = self.image.scale;
+            args.logical = self.image.logical.unwrap_or_default();
+        }
+
+        self.apply_beautify(&mut args.beautify_args);
+
+        // the editor is used when no command is given, and by the rofi menu
+        if let Some(editor) = &self.image.editor {
+            match &args.edit {
+                Some(edit) if edit.is_empty() => args.edit = Some(editor.clone()),
+                None if args.rofi_args.rofi && args.ocr.is_none() && !args.qr && !args.decode => {
+                    args.edit = Some(editor.clone());
+                }
+                _ => {}
+            }
+        }
+
+        self.apply_ocr(&mut args.ocr, &mut args.ocr_args);
+    }
+
+    fn apply_beautify(&self, args: &mut BeautifyArgs) {
+        apply_flag(
+            &mut args.beautify,
+            args.no_beautify,
+            self.image.beautify.enabled,
+        );
+        args.padding = args.padding.or(self.image.beautify.padding);
+        args.background = args
+            .background
+            .take()
+            .or_else(|| self.image.beautify.background.clone());
+        args.radius = args.radius.or(self.image.beautify.radius);
+        args.shadow = args.shadow.or(self.image.beautify.shadow);
+    }
+
+    fn apply_ocr(&self, lang: &mut Option<String>, ocr: &mut OcrArgs) {
+        if let Some(ocr_lang) = &self.image.ocr_lang
+            && lang.as_ref().is_some_and(String::is_empty)
+        {
+            *lang = Some(ocr_lang.clone());
+        }
+
+        ocr.ocr_psm = ocr.ocr_psm.or(self.image.ocr_psm);
+        ocr.ocr_format = ocr.ocr_format.or_else(|| {
+            self.image
+                .ocr_format
+                .as_deref()
+                .map(|format| parse_value("ocr format", format))
+        });
+        if ocr.ocr_cleanup.is_empty() {
+            ocr.ocr_cleanup = self.ocr_cleanup();
+        }
+        apply_flag(
+            &mut ocr.ocr_preprocess,
+            ocr.no_ocr_preprocess,
+            self.image.ocr_preprocess,
+        );
+    }
+
+    fn ocr_cleanup(&self) -> Vec<OcrCleanup> {
+        self.image
+            .ocr_cleanup
+            .iter()
+            .flatten()
+            .map(|cleanup| parse_value("ocr cleanup", cleanup))
+            .collect()
+    }
+
+    /// ocr options from the config file, used when there are no command line options
+    pub fn ocr(&self, lang: Option<String>) -> Ocr {
+        Ocr {
+            lang: lang.or_else(|| self.image.ocr_lang.clone()),
+            psm: self.image.ocr_psm,
+            format: self
+                .image
+                .ocr_format
+                .as_deref()
+                .map(|format| parse_value("ocr format", format))
+                .unwrap_or_default(),
+            cleanup: self.ocr_cleanup(),
+            preprocess: self.image.ocr_preprocess.unwrap_or_default(),
+        }
+    }
+
+    pub fn apply_video(&self, args: &mut VideoArgs) {
+        self.apply_common(&mut args.common_args);
+        self.apply_rofi(&mut args.rofi_args);
+
+        args.duration = args.duration.or(self.video.duration);
+
+        if args.common_args.copy.is_empty()
+            && let Some(copy) = &self.video.copy
+        {
+            args.common_args.copy = parse_copy_modes(copy);
+        }
+
+        if let Some(device) = &self.video.audio_device
+            && args.audio.as_ref().is_some_and(String::is_empty)
+        {
+            args.audio = Some(device.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::image::OcrFormat;
+
+    #[test]
+    fn test_profiles() {
+        let config = Config::parse(
+            r#"
+            delay = 3
+            no-notify = true
+
+            [image]
+            editor = "swappy"
+            ocr-cleanup = ["join-lines", "whitespace"]
+
+            [profiles.docs]
+            delay = 0
+
+            [[hooks]]
+            command = "echo global"
+
+            [profiles.docs.image]
+            ocr-lang = "eng+deu"
+            ocr-format = "markdown"
+
+            [[profiles.docs.hooks]]
+            command = "echo docs"
+            background = true
+            kind = "image"
+            "#,
+        )
+        .expect("failed to parse config");
+
+        assert_eq!(config.delay, Some(3));
+        assert_eq!(config.image.editor.as_deref(), Some("swappy"));
+
+        let docs = config.with_profile("docs").expect("docs profile not found");
+        assert_eq!(docs.delay, Some(0), "profile should override delay");
+        assert_eq!(
+            docs.no_notify,
+            Some(true),
+            "profile should inherit no-notify"
+        );
+        assert_eq!(docs.image.editor.as_deref(), Some("swappy"));
+        assert_eq!(
+            docs.ocr(None),
+            Ocr {
+                lang: Some("eng+deu".to_string()),
+                format: OcrFormat::Markdown,
+                cleanup: vec![OcrCleanup::JoinLines, OcrCleanup::Whitespace],
+                ..Default::default()
+            },
+            "profile should inherit the ocr cleanup"
+        );
+        assert_eq!(docs.hooks.len(), 2, "profile hooks should be added");
+
+        assert!(config.with_profile("missing").is_none());
+    }
+
+    #[test]
+    fn test_flag_overrides() {
+        use crate::cli::focal::{Cli, FocalSubcommand};
+        use clap::Parser;
+
+        let config = Config::parse(
+            r"
+            no-notify = true
+
+            [image]
+            freeze = true
+            ",
+        )
+        .expect("failed to parse config");
+
+        let image_args = |cmd: &str| {
+            let Ok(Cli {
+                command: FocalSubcommand::Image(mut args),
+            }) = Cli::try_parse_from(cmd.split_whitespace())
+            else {
+                panic!("failed to parse {cmd}");
+            };
+            config.apply_image(&mut args);
+            args
+        };
+
+        let args = image_args("focal image --selection");
+        assert!(args.freeze && args.common_args.no_notify);
+
+        let args = image_args("focal image --selection --no-freeze --notify");
+        assert!(
+            !args.freeze && !args.common_args.no_notify,
+            "flags should override the config"
+        );
+
+        let args = image_args("focal image --selection --no-freeze --freeze");
+        assert!(args.freeze, "the last flag should be used");
+    }
+
+    #[test]
+    fn test_unknown_keys() {
+        assert!(
+            Config::parse("dealy = 3").is_err(),
+            "typos should be rejected"
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// placeholders that are replaced with the image path
+const PLACEHOLDERS: [&str; 3] = ["${IMAGE}", "$IMAGE", "{}"];
+
+/// commands for editors that save back to the image, the first word is replaced with the given program
+const PRESETS: [(&str, &str); 5] = [
+    ("satty", "satty --filename {} --output-filename {}"),
+    ("swappy", "swappy --file {} --output-file {}"),
+    ("gimp", "gimp {}"),
+    ("krita", "krita {}"),
+    ("pinta", "pinta {}"),
+];
+
+#[derive(Debug)]
+pub struct EditorError {
+    message: String,
+}
+
+impl fmt::Display for EditorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// an editor command, with the image path substituted for $IMAGE or {}
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Editor {
+    words: Vec<String>,
+}
+
+impl Editor {
+    pub fn new(command: &str) -> Result<Self, EditorError> {
+        let mut words = shell_words::split(command).map_err(|err| EditorError {
+            message: format!("Invalid edit command {command}: {err}"),
+        })?;
+
+        if words.is_empty() {
+            return Err(EditorError {
+                message: "Edit command cannot be empty".to_string(),
+            });
+        }
+
+        // a program without arguments can use a preset, e.g. /usr/bin/satty
+        if let [prog] = words.as_slice()
+            && let Some(name) = Path::new(prog).file_name().and_then(|name| name.to_str())
+            && let Some((_, preset)) = PRESETS.iter().find(|(preset, _)| *preset == name)
+        {
+            let prog = prog.clone();
+            words = shell_words::split(preset).expect("invalid editor preset");
+            words[0] = prog;
+        }
+
+        // the image is passed as the last argument if there are no placeholders
+        if !words
+            .iter()
+            .any(|word| PLACEHOLDERS.iter().any(|p| word.contains(p)))
+        {
+            words.push("{}".to_string());
+        }
+
+        Ok(Self { words })
+    }
+
+    /// the program and arguments to edit the image with
+    pub fn args(&self, image: &Path) -> Vec<String> {
+        let image = image.to_string_lossy();
+
+        self.words
+            .iter()
+            .map(|word| {
+                PLACEHOLDERS
+                    .iter()
+                    .fold(word.clone(), |word, p| word.replace(p, &image))
+            })
+            .collect()
+    }
+
+    pub fn command(&self, image: &Path) -> Command {
+        let args = self.args(image);
+
+        let mut cmd = Command::new(&args[0]);
+        cmd.args(&args[1..]).env("IMAGE", PathBuf::from(image));
+        cmd
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(command: &str) -> Vec<String> {
+        Editor::new(command)
+            .expect("invalid edit command")
+            .args(Path::new("/tmp/a b.png"))
+    }
+
+    #[test]
+    fn test_placeholders() {
+        assert_eq!(
+            args("satty --filename $IMAGE --output-filename '${IMAGE}'"),
+            [
+                "satty",
+                "--filename",
+                "/tmp/a b.png",
+                "--output-filename",
+                "/tmp/a b.png"
+            ]
+        );
+        assert_eq!(
+            args("cp {} /tmp/copy.png"),
+            ["cp", "/tmp/a b.png", "/tmp/copy.png"]
+        );
+        assert_eq!(
+            args("feh --scale-down"),
+            ["feh", "--scale-down", "/tmp/a b.png"],
+            "the image should be appended without placeholders"
+        );
+    }
+
+    #[test]
+    fn test_presets() {
+        assert_eq!(
+            args("/usr/bin/swappy"),
+            [
+                "/usr/bin/swappy",
+                "--file",
+                "/tmp/a b.png",
+                "--output-file",
+                "/tmp/a b.png"
+            ]
+        );
+        assert_eq!(
+            args("mspaint"),
+            ["mspaint", "/tmp/a b.png"],
+            "programs without a preset should be passed the image"
+        );
+        assert_eq!(
+            args("satty --filename {}"),
+            ["satty", "--filename", "/tmp/a b.png"],
+            "presets should not be used with arguments"
+        );
+    }
+
+    #[test]
+    fn test_preset_names() {
+        for name in ["satty", "swappy", "gimp", "krita", "pinta"] {
+            let (_, preset) = PRESETS
+                .iter()
+                .find(|(preset, _)| *preset == name)
+                .unwrap_or_else(|| panic!("{name} should have a preset"));
+            assert!(
+                preset.ends_with(" {}"),
+                "{name} should write back to the image"
+            );
+            assert_eq!(
+                args(name).last().map(String::as_str),
+                Some("/tmp/a b.png"),
+                "{name} should be passed the image to save to"
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(Editor::new("").is_err());
+        assert!(Editor::new("satty 'unclosed").is_err());
+    }
+}
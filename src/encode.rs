@@ -0,0 +1,76 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use image::{
+    DynamicImage, ImageResult,
+    codecs::{
+        avif::AvifEncoder, jpeg::JpegEncoder, png::PngEncoder, qoi::QoiEncoder, webp::WebPEncoder,
+    },
+};
+
+use crate::cli::image::OutputFormat;
+
+/// encodes the image in the given format, quality is only used by lossy formats
+pub fn encode<W: Write>(
+    img: &DynamicImage,
+    format: &OutputFormat,
+    quality: Option<u8>,
+    w: W,
+) -> ImageResult<()> {
+    match format {
+        OutputFormat::Png => img.write_with_encoder(PngEncoder::new(w)),
+        // jpeg does not support transparency
+        OutputFormat::Jpeg => DynamicImage::from(img.to_rgb8())
+            .write_with_encoder(JpegEncoder::new_with_quality(w, quality.unwrap_or(75))),
+        OutputFormat::Webp => {
+            DynamicImage::from(img.to_rgba8()).write_with_encoder(WebPEncoder::new_lossless(w))
+        }
+        // speed 4 is the encoder default
+        OutputFormat::Avif => DynamicImage::from(img.to_rgba8()).write_with_encoder(
+            AvifEncoder::new_with_speed_quality(w, 4, quality.unwrap_or(80)),
+        ),
+        OutputFormat::Qoi => {
+            DynamicImage::from(img.to_rgba8()).write_with_encoder(QoiEncoder::new(w))
+        }
+    }
+}
+
+/// encodes the image to a file
+pub fn save(
+    img: &DynamicImage,
+    format: &OutputFormat,
+    quality: Option<u8>,
+    path: &Path,
+) -> ImageResult<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    encode(img, format, quality, &mut writer)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::ValueEnum;
+
+    #[test]
+    fn test_encode() {
+        let img = DynamicImage::new_rgba8(16, 8);
+
+        for format in OutputFormat::value_variants() {
+            let mut buf = Vec::new();
+            encode(&img, format, Some(50), &mut buf).expect("failed to encode image");
+            assert!(!buf.is_empty(), "{format:?} should not be empty");
+
+            // avif decoding is not supported by the image crate
+            if *format != OutputFormat::Avif {
+                let decoded = image::load_from_memory(&buf).expect("failed to decode image");
+                assert_eq!((decoded.width(), decoded.height()), (16, 8), "{format:?}");
+            }
+        }
+    }
+}
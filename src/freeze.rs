@@ -0,0 +1,171 @@
+use std::{
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    sync::Mutex,
+};
+
+use crate::{check_programs, focal_monitor, image::Grim, is_hyprland};
+
+/// viewers used to show a screenshot fullscreen when no freezer is installed
+const VIEWERS: [&str; 2] = ["imv", "swayimg"];
+
+/// the running freezer and its screenshot, kept globally so it can be stopped on ctrl-c
+static RUNNING: Mutex<Option<(Child, Option<PathBuf>)>> = Mutex::new(None);
+
+/// programs that can freeze the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Hyprpicker,
+    Wayfreeze,
+    /// a grim screenshot shown fullscreen by the viewer
+    Viewer(&'static str),
+}
+
+impl Method {
+    /// picks the freezer for the compositor from the installed programs
+    pub fn detect() -> Option<Self> {
+        Self::choose(is_hyprland(), focal_monitor().all().len(), |prog| {
+            which::which(prog).is_ok()
+        })
+    }
+
+    fn choose(hyprland: bool, monitors: usize, installed: impl Fn(&str) -> bool) -> Option<Self> {
+        // hyprpicker only works on hyprland
+        let mut methods = if hyprland {
+            vec![Self::Hyprpicker, Self::Wayfreeze]
+        } else {
+            vec![Self::Wayfreeze]
+        };
+        // a fullscreen viewer only covers a single monitor
+        if monitors == 1 {
+            methods.extend(VIEWERS.map(Self::Viewer));
+        }
+
+        methods
+            .into_iter()
+            .find(|method| installed(method.program()))
+    }
+
+    const fn program(self) -> &'static str {
+        match self {
+            Self::Hyprpicker => "hyprpicker",
+            Self::Wayfreeze => "wayfreeze",
+            Self::Viewer(viewer) => viewer,
+        }
+    }
+}
+
+/// stops the running freezer, if any
+fn stop() {
+    let running = RUNNING
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .take();
+
+    if let Some((mut child, screenshot)) = running {
+        child.kill().ok();
+        child.wait().ok();
+
+        if let Some(screenshot) = screenshot {
+            std::fs::remove_file(screenshot).ok();
+        }
+    }
+}
+
+/// freezes the screen until dropped
+/// exiting the process skips the drop, so the freezer must be dropped before exiting
+pub struct Freezer;
+
+impl Freezer {
+    /// the zoom lens is only shown by hyprpicker
+    pub fn new(zoom: bool) -> Self {
+        let Some(method) = Method::detect() else {
+            eprintln!(
+                "Freezing the screen requires wayfreeze, hyprpicker on hyprland, or {} to show a screenshot when there is a single monitor.",
+                VIEWERS.join(" or ")
+            );
+            std::process::exit(1);
+        };
+
+        let mut screenshot = None;
+        let mut cmd = Command::new(method.program());
+        match method {
+            Method::Hyprpicker => {
+                cmd.arg(if zoom { "-rz" } else { "-r" });
+            }
+            Method::Wayfreeze => {}
+            Method::Viewer(_) => {
+                check_programs(&["grim"]);
+                let path = dirs::runtime_dir()
+                    .unwrap_or_else(std::env::temp_dir)
+                    .join(format!("focal-freeze-{}.png", std::process::id()));
+                Grim::new(path.clone()).capture();
+
+                cmd.arg("-f").arg(&path);
+                screenshot = Some(path);
+            }
+        }
+
+        let child = cmd
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("could not freeze screen");
+        *RUNNING
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = Some((child, screenshot));
+
+        // the handler can only be set once, video recording sets its own
+        ctrlc::set_handler(|| {
+            stop();
+            std::process::exit(130);
+        })
+        .ok();
+
+        // wait for the frozen screen to be shown
+        std::thread::sleep(std::time::Duration::from_millis(match method {
+            Method::Viewer(_) => 500,
+            _ => 200,
+        }));
+
+        Self
+    }
+}
+
+impl Drop for Freezer {
+    fn drop(&mut self) {
+        stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choose() {
+        let all = |_: &str| true;
+        assert_eq!(Method::choose(true, 1, all), Some(Method::Hyprpicker));
+        assert_eq!(
+            Method::choose(false, 1, all),
+            Some(Method::Wayfreeze),
+            "hyprpicker should only be used on hyprland"
+        );
+
+        let only = |progs: &'static [&'static str]| move |prog: &str| progs.contains(&prog);
+        assert_eq!(
+            Method::choose(true, 1, only(&["wayfreeze"])),
+            Some(Method::Wayfreeze)
+        );
+        assert_eq!(
+            Method::choose(false, 1, only(&["hyprpicker", "swayimg"])),
+            Some(Method::Viewer("swayimg"))
+        );
+        assert_eq!(Method::choose(false, 1, only(&["hyprpicker"])), None);
+        assert_eq!(
+            Method::choose(false, 2, only(&["swayimg"])),
+            None,
+            "viewers should not be used with multiple monitors"
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use chrono::{Local, SecondsFormat};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cli::history::{HistoryArgs, HistoryKind},
+    hooks::CaptureKind,
+    template::CaptureInfo,
+};
+
+/// a single capture in the history
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub path: PathBuf,
+    pub kind: CaptureKind,
+    /// rfc3339 timestamp of when the capture was finished
+    pub time: String,
+    pub area: String,
+    pub monitor: String,
+    pub class: String,
+    pub title: String,
+    pub geometry: String,
+    /// length of the recording in seconds, only for videos
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<u64>,
+}
+
+impl Entry {
+    pub fn new(kind: CaptureKind, path: &Path, info: &CaptureInfo, duration: Option<u64>) -> Self {
+        Self {
+            path: std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf()),
+            kind,
+            time: Local::now().to_rfc3339_opts(SecondsFormat::Secs, false),
+            area: info.area.clone(),
+            monitor: info.monitor.clone(),
+            class: info.app.clone(),
+            title: info.title.clone(),
+            geometry: info.geometry.clone(),
+            duration,
+        }
+    }
+
+    pub const fn is_kind(&self, kind: HistoryKind) -> bool {
+        matches!(
+            (kind, self.kind),
+            (HistoryKind::Image, CaptureKind::Image) | (HistoryKind::Video, CaptureKind::Video)
+        )
+    }
+
+    fn matches(&self, args: &HistoryArgs) -> bool {
+        let kind = args.kind.is_none_or(|kind| self.is_kind(kind));
+        let area = args.area.as_ref().is_none_or(|area| *area == self.area);
+        let app = args
+            .app
+            .as_ref()
+            .is_none_or(|app| self.class.to_lowercase().contains(&app.to_lowercase()));
+
+        kind && area && app
+    }
+}
+
+/// history of captures, stored as json lines
+pub struct History {
+    path: PathBuf,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new(
+            dirs::state_dir()
+                .expect("could not get $XDG_STATE_HOME")
+                .join("focal/history.jsonl"),
+        )
+    }
+}
+
+impl History {
+    pub const fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn record(&self, entry: &Entry) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+
+        let line = serde_json::to_string(entry).map_err(std::io::Error::other)?;
+        writeln!(file, "{line}")
+    }
+
+    /// returns the entries from oldest to newest, removing entries for deleted files
+    pub fn entries(&self) -> std::io::Result<Vec<Entry>> {
+        let content = match std::fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+
+        let lines: Vec<_> = content.lines().filter(|line| !line.is_empty()).collect();
+
+        // invalid lines are dropped along with deleted files
+        let entries: Vec<Entry> = lines
+            .iter()
+            .filter_map(|line| serde_json::from_str::<Entry>(line).ok())
+            .filter(|entry| entry.path.exists())
+            .collect();
+
+        if entries.len() != lines.len() {
+            self.write(&entries)?;
+        }
+
+        Ok(entries)
+    }
+
+    fn write(&self, entries: &[Entry]) -> std::io::Result<()> {
+        let content: String = entries
+            .iter()
+            .map(|entry| serde_json::to_string(entry).map(|line| line + "\n"))
+            .collect::<Result<_, _>>()
+            .map_err(std::io::Error::other)?;
+
+        std::fs::write(&self.path, content)
+    }
+}
+
+/// adds the capture to the history, printing any errors as the capture itself succeeded
+pub fn record(kind: CaptureKind, path: &Path, info: &CaptureInfo, duration: Option<u64>) {
+    if let Err(err) = History::default().record(&Entry::new(kind, path, info, duration)) {
+        eprintln!("Failed to record capture in history: {err}");
+    }
+}
+
+pub fn main(args: &HistoryArgs) {
+    let entries = History::default()
+        .entries()
+        .expect("failed to read capture history");
+
+    // newest first
+    let entries: Vec<_> = entries
+        .into_iter()
+        .rev()
+        .filter(|entry| entry.matches(args))
+        .take(args.limit.unwrap_or(usize::MAX))
+        .collect();
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&entries).expect("failed to serialize history")
+        );
+        return;
+    }
+
+    for entry in entries {
+        println!(
+            "{}\t{}\t{}\t{}",
+            entry.time,
+            entry.kind.as_str(),
+            entry.area,
+            entry.path.display()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history() {
+        let dir = std::env::temp_dir().join(format!("focal-history-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("failed to create test dir");
+
+        let kept = dir.join("kept.png");
+        let deleted = dir.join("deleted.png");
+        std::fs::write(&kept, "").expect("failed to write test file");
+        std::fs::write(&deleted, "").expect("failed to write test file");
+
+        let history = History::new(dir.join("state/history.jsonl"));
+        let info = CaptureInfo {
+            area: "selection".to_string(),
+            app: "firefox".to_string(),
+            ..Default::default()
+        };
+        let entry = |path: &Path| Entry::new(CaptureKind::Image, path, &info, None);
+
+        history.record(&entry(&kept)).expect("failed to record");
+        history.record(&entry(&deleted)).expect("failed to record");
+        assert_eq!(history.entries().expect("failed to read").len(), 2);
+
+        std::fs::remove_file(&deleted).expect("failed to remove test file");
+        let entries = history.entries().expect("failed to read");
+        let content = std::fs::read_to_string(dir.join("state/history.jsonl"))
+            .expect("failed to read history file");
+        std::fs::remove_dir_all(&dir).expect("failed to remove test dir");
+
+        assert_eq!(entries.len(), 1, "deleted files should be pruned");
+        assert_eq!(entries[0].path, kept);
+        assert_eq!(entries[0].class, "firefox");
+        assert_eq!(
+            content.lines().count(),
+            1,
+            "history file should be rewritten"
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use std::{
+    path::Path,
+    process::{Child, Command, ExitStatus},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{cli::focal::CommonArgs, config::Config, show_notification, template::CaptureInfo};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CaptureKind {
+    Image,
+    Video,
+}
+
+impl CaptureKind {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Image => "image",
+            Self::Video => "video",
+        }
+    }
+}
+
+/// a shell command that is run after a capture is finished
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Hook {
+    pub command: String,
+    /// run without waiting for the command to finish before notifying
+    #[serde(default)]
+    pub background: bool,
+    /// only run for this kind of capture, runs for all captures if not set
+    pub kind: Option<CaptureKind>,
+}
+
+/// the finished capture passed to hooks as environment variables
+#[derive(Debug, Clone)]
+pub struct HookContext<'a> {
+    pub kind: CaptureKind,
+    pub path: &'a Path,
+    pub info: &'a CaptureInfo,
+    /// length of the recording in seconds
+    pub duration: Option<u64>,
+}
+
+impl HookContext<'_> {
+    fn envs(&self) -> Vec<(&'static str, String)> {
+        let mut envs = vec![
+            ("FOCAL_PATH", self.path.to_string_lossy().to_string()),
+            ("FOCAL_KIND", self.kind.as_str().to_string()),
+            ("FOCAL_AREA", self.info.area.clone()),
+            ("FOCAL_GEOMETRY", self.info.geometry.clone()),
+            ("FOCAL_MONITOR", self.info.monitor.clone()),
+            ("FOCAL_WINDOW_CLASS", self.info.app.clone()),
+        ];
+
+        if let Some(duration) = self.duration {
+            envs.push(("FOCAL_DURATION", duration.to_string()));
+        }
+
+        envs
+    }
+}
+
+/// hooks that are still running in the background
+#[must_use]
+pub struct BackgroundHooks {
+    children: Vec<(String, Child)>,
+    notify: bool,
+}
+
+impl BackgroundHooks {
+    /// waits for the background hooks to finish, reporting any failures
+    pub fn wait(self) {
+        for (command, mut child) in self.children {
+            match child.wait() {
+                Ok(status) => report(&command, status, self.notify),
+                Err(err) => eprintln!("Failed to wait for hook {command}: {err}"),
+            }
+        }
+    }
+}
+
+fn report(command: &str, status: ExitStatus, notify: bool) {
+    if status.success() {
+        return;
+    }
+
+    let msg = status.code().map_or_else(
+        || format!("Hook {command} was terminated"),
+        |code| format!("Hook {command} failed with exit code {code}"),
+    );
+
+    eprintln!("{msg}");
+    if notify {
+        show_notification(&msg, None);
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Hooks(Vec<Hook>);
+
+impl Hooks {
+    /// hooks from --exec and --exec-background, followed by the hooks in the config file
+    pub fn from_args(args: &CommonArgs, config: &Config, kind: CaptureKind) -> Self {
+        let cli_hooks = |commands: &[String], background| {
+            commands
+                .iter()
+                .map(|command| Hook {
+                    command: command.clone(),
+                    background,
+                    kind: Some(kind),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut hooks = cli_hooks(&args.exec, false);
+        hooks.extend(cli_hooks(&args.exec_background, true));
+        hooks.extend(
+            config
+                .hooks
+                .iter()
+                .filter(|hook| hook.kind.is_none_or(|k| k == kind))
+                .cloned(),
+        );
+
+        Self(hooks)
+    }
+
+    /// runs the blocking hooks in order and starts the background hooks
+    pub fn run(&self, ctx: &HookContext, notify: bool) -> BackgroundHooks {
+        let envs = ctx.envs();
+        let mut children = Vec::new();
+
+        for hook in self
+            .0
+            .iter()
+            .filter(|hook| hook.kind.is_none_or(|k| k == ctx.kind))
+        {
+            let mut cmd = Command::new("sh");
+            cmd.arg("-c").arg(&hook.command).envs(envs.clone());
+
+            if hook.background {
+                match cmd.spawn() {
+                    Ok(child) => children.push((hook.command.clone(), child)),
+                    Err(err) => eprintln!("Failed to run hook {}: {err}", hook.command),
+                }
+            } else {
+                match cmd.status() {
+                    Ok(status) => report(&hook.command, status, notify),
+                    Err(err) => eprintln!("Failed to run hook {}: {err}", hook.command),
+                }
+            }
+        }
+
+        BackgroundHooks { children, notify }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run() {
+        let dir = std::env::temp_dir().join(format!("focal-hooks-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("failed to create test dir");
+        let out = dir.join("env");
+
+        let hook = |command: String, background, kind| Hook {
+            command,
+            background,
+            kind,
+        };
+        let hooks = Hooks(vec![
+            hook(
+                format!(
+                    "echo \"$FOCAL_KIND $FOCAL_AREA $FOCAL_PATH\" >> {}",
+                    out.display()
+                ),
+                false,
+                None,
+            ),
+            hook(
+                format!("echo video >> {}", out.display()),
+                false,
+                Some(CaptureKind::Video),
+            ),
+            hook(format!("echo bg >> {}", out.display()), true, None),
+        ]);
+
+        let info = CaptureInfo {
+            area: "monitor".to_string(),
+            ..Default::default()
+        };
+        hooks
+            .run(
+                &HookContext {
+                    kind: CaptureKind::Image,
+                    path: Path::new("/tmp/a.png"),
+                    info: &info,
+                    duration: None,
+                },
+                false,
+            )
+            .wait();
+
+        let content = std::fs::read_to_string(&out).expect("hooks did not run");
+        std::fs::remove_dir_all(&dir).expect("failed to remove test dir");
+
+        assert_eq!(
+            content, "image monitor /tmp/a.png\nbg\n",
+            "hooks for other kinds should not run"
+        );
+    }
+}
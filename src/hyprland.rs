@@ -1,31 +1,41 @@
 use hyprland::{
-    data::{Clients, Monitor, Monitors, Transforms},
-    shared::{HyprData, HyprDataActive},
+    data::{Client, Clients, Monitor, Monitors, Transforms},
+    shared::{HyprData, HyprDataActive, HyprDataActiveOptional},
 };
 
 use crate::{
     SlurpGeom,
-    monitor::{FocalMonitor, FocalMonitors, Rotation},
+    monitor::{FocalMonitor, FocalMonitors, FocalWindow, Rotation, logical_size},
 };
 
 fn to_focal_monitor(mon: &Monitor) -> FocalMonitor {
+    let rotation = match mon.transform {
+        Transforms::Normal => Rotation::Normal,
+        Transforms::Normal90 => Rotation::Normal90,
+        Transforms::Normal180 => Rotation::Normal180,
+        Transforms::Normal270 => Rotation::Normal270,
+        Transforms::Flipped => Rotation::Flipped,
+        Transforms::Flipped90 => Rotation::Flipped90,
+        Transforms::Flipped180 => Rotation::Flipped180,
+        Transforms::Flipped270 => Rotation::Flipped270,
+    };
+    let (w, h) = logical_size(mon.width.into(), mon.height.into(), mon.scale, &rotation);
+
     FocalMonitor {
         name: mon.name.clone(),
         x: mon.x,
         y: mon.y,
-        w: mon.width.into(),
-        h: mon.height.into(),
+        w,
+        h,
         scale: mon.scale,
-        rotation: match mon.transform {
-            Transforms::Normal => Rotation::Normal,
-            Transforms::Normal90 => Rotation::Normal90,
-            Transforms::Normal180 => Rotation::Normal180,
-            Transforms::Normal270 => Rotation::Normal270,
-            Transforms::Flipped => Rotation::Flipped,
-            Transforms::Flipped90 => Rotation::Flipped90,
-            Transforms::Flipped180 => Rotation::Flipped180,
-            Transforms::Flipped270 => Rotation::Flipped270,
-        },
+        rotation,
+    }
+}
+
+fn to_focal_window(win: &Client) -> FocalWindow {
+    FocalWindow {
+        class: win.class.clone(),
+        title: win.title.clone(),
     }
 }
 
@@ -44,7 +54,7 @@ impl FocalMonitors for HyprMonitors {
         to_focal_monitor(&Monitor::get_active().expect("unable to get active monitor"))
     }
 
-    fn window_geoms(&self) -> Vec<SlurpGeom> {
+    fn windows(&self) -> Vec<(SlurpGeom, FocalWindow)> {
         let active_wksps: Vec<_> = Monitors::get()
             .expect("unable to get monitors")
             .iter()
@@ -56,13 +66,23 @@ impl FocalMonitors for HyprMonitors {
             windows
                 .iter()
                 .filter(|&win| active_wksps.contains(&win.workspace.id))
-                .map(|win| SlurpGeom {
-                    x: win.at.0.into(),
-                    y: win.at.1.into(),
-                    w: win.size.0.into(),
-                    h: win.size.1.into(),
+                .map(|win| {
+                    let geom = SlurpGeom {
+                        x: win.at.0.into(),
+                        y: win.at.1.into(),
+                        w: win.size.0.into(),
+                        h: win.size.1.into(),
+                    };
+                    (geom, to_focal_window(win))
                 })
                 .collect()
         })
     }
+
+    fn active_window(&self) -> Option<FocalWindow> {
+        Client::get_active()
+            .ok()
+            .flatten()
+            .map(|win| to_focal_window(&win))
+    }
 }
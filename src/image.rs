@@ -1,24 +1,45 @@
 use std::{
-    path::PathBuf,
+    io::Write,
+    path::{Path, PathBuf},
     process::{Command, Stdio},
+    time::Duration,
 };
 
 use crate::{
-    Rofi, check_programs,
+    Rofi,
+    barcode::{self, Barcode},
+    beautify::Beautify,
+    check_programs,
     cli::{
-        focal::Cli,
-        image::{CaptureArea, ImageArgs},
+        focal::{Cli, CopyMode},
+        image::{CaptureArea, ImageArgs, OutputFormat, ScrollInput},
     },
-    create_parent_dirs, focal_monitor, is_hyprland, is_mango, is_niri, iso8601_filename,
-    show_notification,
+    clipboard::Clipboard,
+    config::Config,
+    editor::Editor,
+    encode, focal_monitor,
+    freeze::Freezer,
+    history,
+    hooks::{BackgroundHooks, CaptureKind, HookContext, Hooks},
+    interval::{self, Interval, IntervalLock},
+    is_hyprland, is_mango, is_niri, move_file,
+    ocr::Ocr,
+    resize,
+    screencopy::{Screencopy, ScreencopyError},
+    scroll, show_notification,
+    template::{CaptureInfo, FilenameTemplate},
+    upload::Upload,
 };
+use ::image::{DynamicImage, RgbaImage};
 use clap::CommandFactory;
 use execute::Execute;
 
 #[derive(Default)]
-struct Grim {
+pub struct Grim {
     monitor: String,
     geometry: String,
+    scale: Option<f64>,
+    cursor: bool,
     output: PathBuf,
 }
 
@@ -30,19 +51,41 @@ impl Grim {
         }
     }
 
+    #[must_use]
     pub fn geometry(mut self, geometry: &str) -> Self {
         self.geometry = geometry.to_string();
         self
     }
 
+    #[must_use]
     pub fn monitor(mut self, monitor: &str) -> Self {
         self.monitor = monitor.to_string();
         self
     }
 
-    pub fn capture(&self) {
+    #[must_use]
+    pub const fn scale(mut self, scale: Option<f64>) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    #[must_use]
+    pub const fn cursor(mut self, cursor: bool) -> Self {
+        self.cursor = cursor;
+        self
+    }
+
+    fn command(&self) -> Command {
         let mut grim = Command::new("grim");
 
+        if self.cursor {
+            grim.arg("-c");
+        }
+
+        if let Some(scale) = self.scale {
+            grim.arg("-s").arg(scale.to_string());
+        }
+
         if !self.monitor.is_empty() {
             grim.arg("-o").arg(&self.monitor);
         }
@@ -51,18 +94,77 @@ impl Grim {
             grim.arg("-g").arg(&self.geometry);
         }
 
-        grim.arg(&self.output)
-            .execute()
+        grim.arg(&self.output);
+        grim
+    }
+
+    pub fn capture(&self) {
+        self.command().execute().expect("unable to execute grim");
+    }
+
+    /// captures to stdout, returning the png or None if nothing was captured
+    pub fn try_capture_bytes(&self) -> Option<Vec<u8>> {
+        let output = self
+            .command()
+            .stdout(Stdio::piped())
+            .execute_output()
             .expect("unable to execute grim");
+
+        (output.status.success() && !output.stdout.is_empty()).then_some(output.stdout)
     }
 
-    /// show a notification
-    pub fn notify(&self) {
-        show_notification(
-            &format!("Screenshot captured to {}", &self.output.display()),
-            Some(&self.output),
-        );
+    /// captures to stdout, returning the png and exiting if nothing was captured
+    pub fn capture_bytes(&self) -> Vec<u8> {
+        self.try_capture_bytes().unwrap_or_else(|| {
+            eprintln!("No image was captured by grim!");
+            std::process::exit(1);
+        })
+    }
+}
+
+/// temporary file that captures are written to before being saved
+fn capture_path() -> PathBuf {
+    dirs::runtime_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join(format!("focal-{}.png", std::process::id()))
+}
+
+/// grim is only needed when the compositor cannot be captured natively, so it is checked here
+/// failures are only reported if the compositor supports capturing natively
+fn fall_back_to_grim(err: &ScreencopyError) {
+    if !err.is_unsupported() {
+        eprintln!("Unable to capture natively, falling back to grim: {err}");
     }
+
+    check_programs(&["grim"]);
+}
+
+/// temporary png that is scanned for text or barcodes when the output format cannot be read
+fn scan_path() -> PathBuf {
+    dirs::runtime_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join(format!("focal-scan-{}.png", std::process::id()))
+}
+
+/// waits for the captured image to appear, returning false if it never did
+fn wait_for_capture(path: &Path, timeout: Duration) -> bool {
+    let interval = Duration::from_millis(100);
+
+    let mut waited = Duration::ZERO;
+    while !path.exists() && waited < timeout {
+        std::thread::sleep(interval);
+        waited += interval;
+    }
+
+    path.exists()
+}
+
+/// edits the image in place
+pub fn edit(editor: &Editor, image: &Path) {
+    editor
+        .command(image)
+        .execute()
+        .expect("Failed to edit screenshot");
 }
 
 #[allow(clippy::struct_excessive_bools)]
@@ -70,61 +172,311 @@ pub struct Screenshot {
     pub delay: Option<u64>,
     pub no_rounded_windows: bool,
     pub freeze: bool,
-    pub edit: Option<String>,
+    pub edit: Option<Editor>,
     pub icons: bool,
     pub notify: bool,
     pub slurp: Option<String>,
-    pub ocr: Option<String>,
-    pub output: PathBuf,
+    pub ocr: Option<Ocr>,
+    pub barcode: Option<Barcode>,
+    pub format: OutputFormat,
+    pub quality: Option<u8>,
+    pub scale: Option<f64>,
+    pub logical: bool,
+    pub max_width: Option<u32>,
+    pub max_height: Option<u32>,
+    pub cursor: bool,
+    pub copy: Vec<CopyMode>,
+    pub primary: bool,
+    pub beautify: Option<Beautify>,
+    pub hooks: Hooks,
+    pub upload: Option<Upload>,
+    /// writes the screenshot to stdout instead of a file
+    pub stdout: bool,
+    pub template: FilenameTemplate,
 }
 
 impl Screenshot {
-    fn edit_or_ocr(&self) {
-        if self.ocr.is_some() {
-            self.ocr();
+    /// if the image is scanned for text or barcodes instead of being edited and copied
+    const fn is_scanned(&self) -> bool {
+        self.ocr.is_some() || self.barcode.is_some()
+    }
+
+    fn edit_or_ocr(&self, output: &Path) {
+        if self.is_scanned() {
+            // the png written by save_and_scan is scanned if the output cannot be read
+            let input = if self.format.is_scannable() {
+                output.to_path_buf()
+            } else {
+                scan_path()
+            };
+
+            if let Some(ocr) = &self.ocr {
+                ocr.run(&input, self.primary, self.notify);
+            } else if let Some(barcode) = self.barcode {
+                barcode::decode(&input, barcode, self.primary, self.notify);
+            }
+
+            if input != output {
+                std::fs::remove_file(&input).expect("failed to remove scanned image");
+            }
         } else {
             if self.edit.is_some() {
-                self.edit();
+                self.edit(output);
             }
 
-            // copying not needed for niri, since it already does that
-            if !is_niri() {
-                let mut img = std::fs::File::open(&self.output).expect("failed to open image");
-                Command::new("wl-copy")
-                    .arg("--type")
-                    .arg("image/png")
-                    .execute_input_reader(&mut img)
-                    .expect("failed to copy image to clipboard");
-            }
+            // niri already copies the original capture, but it is replaced to offer the saved file
+            Clipboard::new()
+                .primary(self.primary)
+                .modes(&self.copy, output, self.format.mime_type())
+                .copy();
+        }
+    }
+
+    /// scale factor passed to grim, grim captures at the logical size with a scale of 1
+    fn grim_scale(&self) -> Option<f64> {
+        self.scale.or_else(|| self.logical.then_some(1.0))
+    }
+
+    /// scale factor for captures that cannot be scaled by grim
+    fn resize_factor(&self) -> Option<f64> {
+        if !is_niri() {
+            return None;
+        }
+
+        self.scale.or_else(|| {
+            self.logical
+                .then(|| 1.0 / f64::from(focal_monitor().focused().scale))
+        })
+    }
+
+    /// if the captured image needs to be modified before saving
+    fn is_processed(&self) -> bool {
+        self.format != OutputFormat::Png
+            || self.beautify.is_some()
+            || self.max_width.is_some()
+            || self.max_height.is_some()
+            || self.resize_factor().is_some()
+    }
+
+    /// applies the scaling and beautify options to the captured image
+    fn process(&self, mut img: DynamicImage) -> DynamicImage {
+        if let Some(factor) = self.resize_factor() {
+            img = resize::scale(&img, factor);
+        }
+
+        img = resize::fit(img, self.max_width, self.max_height);
+
+        // beautify last so the padding is not scaled
+        if let Some(beautify) = &self.beautify {
+            img = beautify.apply(&img);
+        }
+
+        img
+    }
+
+    /// writes the captured png to stdout, processing it if needed
+    fn write_stdout(&self, png: &[u8]) {
+        if self.is_processed() {
+            let img = ::image::load_from_memory_with_format(png, ::image::ImageFormat::Png)
+                .expect("failed to read captured image");
+            self.write_stdout_image(img);
+            return;
+        }
+
+        let mut stdout = std::io::stdout().lock();
+        stdout
+            .write_all(png)
+            .and_then(|()| stdout.flush())
+            .expect("failed to write screenshot to stdout");
+    }
+
+    /// encodes the captured image to stdout after processing it
+    fn write_stdout_image(&self, img: DynamicImage) {
+        let mut stdout = std::io::stdout().lock();
+        encode::encode(&self.process(img), &self.format, self.quality, &mut stdout)
+            .expect("failed to write screenshot to stdout");
+        stdout
+            .flush()
+            .expect("failed to write screenshot to stdout");
+    }
+
+    /// saves the captured image to the path given by the filename template, processing it if needed
+    /// returns the path along with the info updated with the final dimensions
+    fn save(&self, captured: &Path, info: CaptureInfo) -> (PathBuf, CaptureInfo) {
+        // captures are already png, so they can be saved as is
+        if !self.is_processed() {
+            let (w, h) =
+                ::image::image_dimensions(captured).expect("failed to read captured image");
+
+            let info = info.dimensions(w, h);
+            let output = self.template.path(&info);
+            move_file(captured, &output);
+            return (output, info);
+        }
+
+        let img = ::image::open(captured).expect("failed to read captured image");
+        std::fs::remove_file(captured).expect("failed to remove captured image");
+
+        self.save_and_scan(img, info)
+    }
+
+    /// processes and saves the image to the path given by the filename template
+    fn save_image(&self, img: DynamicImage, info: CaptureInfo) -> (PathBuf, CaptureInfo) {
+        let img = self.process(img);
+
+        let info = info.dimensions(img.width(), img.height());
+        let output = self.template.path(&info);
+        encode::save(&img, &self.format, self.quality, &output).expect("failed to save screenshot");
+
+        (output, info)
+    }
+
+    /// saves the image, also writing the png that is scanned if the output format cannot be read
+    /// only used before the post capture steps, which remove the png after scanning it
+    fn save_and_scan(&self, img: DynamicImage, info: CaptureInfo) -> (PathBuf, CaptureInfo) {
+        if self.is_scanned() && !self.format.is_scannable() {
+            img.save_with_format(scan_path(), ::image::ImageFormat::Png)
+                .expect("failed to save image to scan");
         }
+
+        self.save_image(img, info)
+    }
+
+    /// edits, copies, uploads and runs the hooks for the saved image
+    /// returns the uploaded url and the hooks still running in the background
+    fn post_save(&self, output: &Path, info: &CaptureInfo) -> (Option<String>, BackgroundHooks) {
+        self.edit_or_ocr(output);
+        self.record(output, info)
+    }
+
+    /// records, uploads and runs the hooks for the saved image
+    fn record(&self, output: &Path, info: &CaptureInfo) -> (Option<String>, BackgroundHooks) {
+        history::record(CaptureKind::Image, output, info, None);
+
+        let url = self.upload.as_ref().and_then(|upload| {
+            upload.upload_and_copy(output, self.format.mime_type(), self.primary, self.notify)
+        });
+
+        let background = self.hooks.run(
+            &HookContext {
+                kind: CaptureKind::Image,
+                path: output,
+                info,
+                duration: None,
+            },
+            self.notify,
+        );
+
+        (url, background)
+    }
+
+    /// saves an existing image and runs the post capture steps, used by focal process
+    pub fn process_image(&self, img: DynamicImage, info: CaptureInfo) {
+        let (output, info) = self.save_and_scan(img, info);
+        let (url, background) = self.post_save(&output, &info);
+
+        if !self.is_scanned() && self.notify {
+            show_notification(
+                &url.map_or_else(
+                    || format!("Image saved to {}", output.display()),
+                    |url| format!("Image uploaded to {url}"),
+                ),
+                Some(&output),
+            );
+        }
+
+        background.wait();
     }
 
-    fn capture(&self, monitor: &str, geometry: &str) {
+    /// saves the captured image and runs the post capture steps
+    fn finish(&self, captured: &Path, info: CaptureInfo) {
+        if self.stdout {
+            let png = std::fs::read(captured).expect("failed to read captured image");
+            std::fs::remove_file(captured).expect("failed to remove captured image");
+            self.write_stdout(&png);
+            return;
+        }
+
+        let (output, info) = self.save(captured, info);
+        // niri shows its own screenshot notification
+        self.notify_captured(&output, &info, is_niri());
+    }
+
+    /// saves the image captured in memory and runs the post capture steps
+    fn finish_image(&self, img: DynamicImage, info: CaptureInfo) {
+        if self.stdout {
+            self.write_stdout_image(img);
+            return;
+        }
+
+        let (output, info) = self.save_and_scan(img, info);
+        self.notify_captured(&output, &info, false);
+    }
+
+    /// runs the post capture steps for the saved capture and shows a notification
+    /// the notification is skipped if the compositor already showed one
+    fn notify_captured(&self, output: &Path, info: &CaptureInfo, notified: bool) {
+        let (url, background) = self.post_save(output, info);
+
+        if !self.is_scanned() && self.notify && (!notified || url.is_some()) {
+            show_notification(
+                &url.map_or_else(
+                    || format!("Screenshot captured to {}", output.display()),
+                    |url| format!("Screenshot uploaded to {url}"),
+                ),
+                Some(&output.to_path_buf()),
+            );
+        }
+
+        background.wait();
+    }
+
+    fn capture(&self, info: CaptureInfo, monitor: &str, geometry: &str) {
         // small delay before capture
         std::thread::sleep(std::time::Duration::from_millis(500));
 
-        let grim = Grim::new(self.output.clone())
+        match Screencopy::default()
             .geometry(geometry)
-            .monitor(monitor);
+            .monitor(monitor)
+            .scale(self.grim_scale())
+            .cursor(self.cursor)
+            .capture()
+        {
+            Ok(img) => {
+                self.finish_image(img, info);
+                return;
+            }
+            Err(err) => fall_back_to_grim(&err),
+        }
 
-        grim.capture();
+        if self.stdout {
+            let png = Grim::new(PathBuf::from("-"))
+                .geometry(geometry)
+                .monitor(monitor)
+                .scale(self.grim_scale())
+                .cursor(self.cursor)
+                .capture_bytes();
 
-        // wait for up to 5s for the file to appear
-        let mut attempts = 0;
-        while !self.output.exists() && attempts < 50 {
-            std::thread::sleep(std::time::Duration::from_millis(100));
-            attempts += 1;
+            self.write_stdout(&png);
+            return;
         }
-        if !self.output.exists() {
+
+        let captured = capture_path();
+
+        Grim::new(captured.clone())
+            .geometry(geometry)
+            .monitor(monitor)
+            .scale(self.grim_scale())
+            .cursor(self.cursor)
+            .capture();
+
+        // wait for up to 5s for the file to appear
+        if !wait_for_capture(&captured, Duration::from_secs(5)) {
             eprintln!("No image was captured by grim!");
             std::process::exit(1);
         }
 
-        self.edit_or_ocr();
-
-        if self.ocr.is_none() && self.notify {
-            grim.notify();
-        }
+        self.finish(&captured, info);
     }
 
     pub fn monitor(&self) {
@@ -134,25 +486,30 @@ impl Screenshot {
         if is_niri() {
             use niri_ipc::{Action, Request, socket::Socket};
 
+            let info = CaptureInfo::new("monitor").monitor(&focal_monitor().focused());
+            let captured = capture_path();
+
             let mut socket = Socket::connect().expect("failed to connect to niri socket");
             socket
                 .send(Request::Action(Action::ScreenshotScreen {
-                    path: Some(
-                        self.output
-                            .to_str()
-                            .expect("invalid output path")
-                            .to_string(),
-                    ),
-                    show_pointer: false,
+                    path: Some(captured.to_str().expect("invalid output path").to_string()),
+                    show_pointer: self.cursor,
                     write_to_disk: true,
                 }))
                 .expect("failed to send ScreenshotScreen request to niri")
                 .expect("failed to screenshot screen");
 
-            self.edit_or_ocr();
+            if !wait_for_capture(&captured, Duration::from_secs(5)) {
+                eprintln!("No image was captured by niri!");
+                std::process::exit(1);
+            }
+
+            self.finish(&captured, info);
         } else {
             std::thread::sleep(std::time::Duration::from_secs(self.delay.unwrap_or(0)));
-            self.capture(&focal_monitor().focused().name, "");
+
+            let mon = focal_monitor().focused();
+            self.capture(CaptureInfo::new("monitor").monitor(&mon), &mon.name, "");
         }
     }
 
@@ -167,24 +524,36 @@ impl Screenshot {
             panic!("unexpected response from niri, should be PickWindow");
         };
 
-        let Some(Window { id, .. }) = window else {
+        let Some(Window {
+            id, app_id, title, ..
+        }) = window
+        else {
             eprintln!("No window was picked.");
             std::process::exit(1);
         };
 
+        let info = CaptureInfo {
+            app: app_id.unwrap_or_default(),
+            title: title.unwrap_or_default(),
+            ..CaptureInfo::new("window").monitor(&focal_monitor().focused())
+        };
+        let captured = capture_path();
+
         socket
             .send(Request::Action(Action::ScreenshotWindow {
                 id: Some(id),
-                path: Some(
-                    self.output
-                        .to_str()
-                        .expect("invalid output path")
-                        .to_string(),
-                ),
+                path: Some(captured.to_str().expect("invalid output path").to_string()),
                 write_to_disk: true,
             }))
             .expect("failed to send ScreenshotWindow request to niri")
             .expect("failed to screenshot window");
+
+        if !wait_for_capture(&captured, Duration::from_secs(5)) {
+            eprintln!("No image was captured by niri!");
+            std::process::exit(1);
+        }
+
+        self.finish(&captured, info);
     }
 
     fn mango_window(&self) {
@@ -222,7 +591,13 @@ impl Screenshot {
         }
 
         std::thread::sleep(std::time::Duration::from_secs(delay));
-        self.capture("", &geom.to_string());
+        self.capture(
+            CaptureInfo::new("window")
+                .window(focal_monitor().active_window().unwrap_or_default())
+                .geometry(&geom),
+            "",
+            &geom.to_string(),
+        );
     }
 
     pub fn window(&self) {
@@ -240,21 +615,25 @@ impl Screenshot {
 
         std::thread::sleep(std::time::Duration::from_secs(delay));
 
+        let info = CaptureInfo::new("selection").monitor(&focal_monitor().focused());
+        let captured = capture_path();
+
         let mut socket = Socket::connect().expect("failed to connect to niri socket");
         socket
             .send(Request::Action(Action::Screenshot {
-                path: Some(
-                    self.output
-                        .to_str()
-                        .expect("invalid output path")
-                        .to_string(),
-                ),
-                show_pointer: false,
+                path: Some(captured.to_str().expect("invalid output path").to_string()),
+                show_pointer: self.cursor,
             }))
             .expect("failed to send Screenshot request to niri")
             .expect("failed to capture screenshot");
 
-        self.edit_or_ocr();
+        // the screenshot ui is interactive, so allow more time for a selection to be made
+        if !wait_for_capture(&captured, Duration::from_mins(2)) {
+            eprintln!("No screenshot selection was made.");
+            std::process::exit(1);
+        }
+
+        self.finish(&captured, info);
     }
 
     pub fn selection(&self) {
@@ -264,25 +643,21 @@ impl Screenshot {
             self.niri_selection(delay);
         } else {
             // freeze screen before delay to capture selection
-            let picker_process = if self.freeze || delay > 0 {
-                let child = Command::new("hyprpicker")
-                    .arg("-rz")
-                    .spawn()
-                    .expect("could not freeze screen");
-                std::thread::sleep(std::time::Duration::from_millis(200));
-                Some(child)
-            } else {
-                None
-            };
+            let freezer = (self.freeze || delay > 0).then(|| Freezer::new(true));
 
-            let (geom, is_window) = crate::SlurpGeom::prompt(self.slurp.as_deref());
-            // kill hyprpicker so it doesn't ask for a color
-            picker_process.map(|mut p| p.kill().ok());
+            let selection = crate::SlurpGeom::prompt(self.slurp.as_deref());
+            // unfreeze before capturing, also when the selection was cancelled
+            drop(freezer);
+            let Some((geom, window)) = selection else {
+                std::process::exit(1);
+            };
+            let is_window = window.is_some();
 
             std::thread::sleep(std::time::Duration::from_secs(delay));
 
+            let info = CaptureInfo::selection(window).geometry(&geom);
             let do_capture = || {
-                self.capture("", &geom.to_string());
+                self.capture(info.clone(), "", &geom.to_string());
             };
 
             if is_hyprland() && is_window && self.no_rounded_windows {
@@ -304,68 +679,300 @@ impl Screenshot {
         }
     }
 
-    pub fn all(&self) {
-        if is_niri() || is_mango() {
-            unimplemented!("Capturing all screens is not supported");
+    /// captures into memory, without any of the post capture steps
+    fn capture_image(&self, monitor: &str, geometry: &str, cursor: bool) -> RgbaImage {
+        // captures on niri are scaled when processing, as for its own screenshots
+        let scale = if is_niri() { None } else { self.grim_scale() };
+        Self::capture_scaled(monitor, geometry, scale, cursor)
+    }
+
+    /// captures into memory at the given scale, or the scale of the monitors if None
+    fn capture_scaled(
+        monitor: &str,
+        geometry: &str,
+        scale: Option<f64>,
+        cursor: bool,
+    ) -> RgbaImage {
+        Screencopy::default()
+            .monitor(monitor)
+            .geometry(geometry)
+            .scale(scale)
+            .cursor(cursor)
+            .capture()
+            .unwrap_or_else(|err| {
+                fall_back_to_grim(&err);
+                let png = Grim::new(PathBuf::from("-"))
+                    .monitor(monitor)
+                    .geometry(geometry)
+                    .scale(scale)
+                    .cursor(cursor)
+                    .capture_bytes();
+                ::image::load_from_memory(&png).expect("failed to read captured image")
+            })
+            .to_rgba8()
+    }
+
+    /// captures the selection while it is scrolled, stitching the frames into one image
+    pub fn scroll(&self, input: Option<ScrollInput>) {
+        let Some((geom, _)) = crate::SlurpGeom::prompt(self.slurp.as_deref()) else {
+            std::process::exit(1);
+        };
+
+        std::thread::sleep(std::time::Duration::from_secs(self.delay.unwrap_or(0)));
+
+        if input.is_none() {
+            eprintln!(
+                "Scroll the selection, capturing stops once it has not changed for a few seconds."
+            );
         }
 
-        let (w, h) = focal_monitor().total_dimensions();
+        // the cursor stays in place while the content moves, so it is never captured
+        let geometry = geom.to_string();
+        let frames = scroll::capture_frames(|| self.capture_image("", &geometry, false), input);
+
+        let Some(stitched) = scroll::stitch(&frames) else {
+            eprintln!("No frames were captured.");
+            std::process::exit(1);
+        };
+
+        self.finish_image(
+            DynamicImage::ImageRgba8(stitched),
+            CaptureInfo::new("scroll").geometry(&geom),
+        );
+    }
+
+    /// captures the area repeatedly into numbered files until the count is reached or it is stopped
+    pub fn repeat(&self, area: &CaptureArea, interval: &Interval) {
+        // the area is chosen once, so every capture is of the same region
+        let (info, monitor, geometry) = match area {
+            CaptureArea::Monitor => {
+                let mon = focal_monitor().focused();
+                (
+                    CaptureInfo::new("monitor").monitor(&mon),
+                    mon.name,
+                    String::new(),
+                )
+            }
+            CaptureArea::Selection | CaptureArea::Window => {
+                let Some((geom, window)) = crate::SlurpGeom::prompt(self.slurp.as_deref()) else {
+                    std::process::exit(1);
+                };
+                (
+                    CaptureInfo::selection(window).geometry(&geom),
+                    String::new(),
+                    geom.to_string(),
+                )
+            }
+            CaptureArea::All => (CaptureInfo::new("all"), String::new(), String::new()),
+            CaptureArea::EachMonitor => {
+                unreachable!("each-monitor cannot be captured at an interval")
+            }
+        };
 
         std::thread::sleep(std::time::Duration::from_secs(self.delay.unwrap_or(0)));
-        self.capture("", &format!("0,0 {w}x{h}"));
-    }
-
-    fn edit(&self) {
-        if let Some(prog) = &self.edit {
-            if prog.ends_with("swappy") {
-                Command::new("swappy")
-                    .arg("--file")
-                    .arg(self.output.clone())
-                    .arg("--output-file")
-                    .arg(self.output.clone())
-                    .execute()
-                    .expect("Failed to edit screenshot with swappy");
-            } else {
-                std::process::Command::new(prog)
-                    .arg(self.output.clone())
-                    .execute()
-                    .expect("Failed to edit screenshot");
+
+        IntervalLock::create();
+        // stop capturing gracefully, so the timelapse is still created
+        ctrlc::set_handler(|| {
+            IntervalLock::remove();
+        })
+        .ok();
+
+        let capture = || self.capture_image(&monitor, &geometry, self.cursor);
+        let save = |img: RgbaImage| {
+            let (output, info) = self.save_image(DynamicImage::ImageRgba8(img), info.clone());
+            let (_, background) = self.record(&output, &info);
+            (output, background)
+        };
+
+        let start = std::time::Instant::now();
+        let mut saved = Vec::new();
+        if interval.is_burst() {
+            // frames are kept in memory until the burst is over, as saving is much slower
+            let mut frames = vec![capture()];
+            while interval.wait(start, u32::try_from(frames.len()).unwrap_or(u32::MAX)) {
+                frames.push(capture());
+            }
+            saved.extend(frames.into_iter().map(save));
+        } else {
+            saved.push(save(capture()));
+            while interval.wait(start, u32::try_from(saved.len()).unwrap_or(u32::MAX)) {
+                saved.push(save(capture()));
             }
         }
+        IntervalLock::remove();
+
+        let (outputs, backgrounds): (Vec<_>, Vec<_>) = saved.into_iter().unzip();
+        self.finish_repeat(&outputs, interval, &info);
+
+        for background in backgrounds {
+            background.wait();
+        }
     }
 
-    fn ocr(&self) {
-        let mut cmd = Command::new("tesseract");
-        cmd.arg(&self.output).arg("-");
+    /// copies the last capture, creates the timelapse and shows a notification
+    fn finish_repeat(&self, outputs: &[PathBuf], interval: &Interval, info: &CaptureInfo) {
+        let Some(last) = outputs.last() else {
+            return;
+        };
 
-        if let Some(lang) = &self.ocr
-            && !lang.is_empty()
-        {
-            cmd.arg("-l").arg(lang);
+        Clipboard::new()
+            .primary(self.primary)
+            .modes(&self.copy, last, self.format.mime_type())
+            .copy();
+
+        let timelapse = interval.timelapse.as_ref().and_then(|timelapse| {
+            let video = timelapse.template.path(info);
+            if !interval::timelapse(outputs, timelapse.fps, &video) {
+                eprintln!("Failed to create the timelapse with ffmpeg.");
+                return None;
+            }
+
+            let frames = u64::try_from(outputs.len()).unwrap_or_default();
+            history::record(
+                CaptureKind::Video,
+                &video,
+                info,
+                Some(frames / u64::from(timelapse.fps)),
+            );
+            Some(video)
+        });
+
+        if self.notify {
+            let dir = last.parent().unwrap_or_else(|| Path::new("/"));
+            let msg = timelapse.map_or_else(
+                || {
+                    format!(
+                        "{} screenshots captured to {}",
+                        outputs.len(),
+                        dir.display()
+                    )
+                },
+                |video| {
+                    format!(
+                        "Timelapse of {} screenshots captured to {}",
+                        outputs.len(),
+                        video.display()
+                    )
+                },
+            );
+            show_notification(&msg, Some(last));
         }
+    }
 
-        let output = cmd
-            .stdout(Stdio::piped())
-            .execute_output()
-            .expect("Failed to run tesseract");
+    /// captures each monitor into a separate file, copying the focused monitor
+    pub fn each_monitor(&self) {
+        std::thread::sleep(std::time::Duration::from_secs(self.delay.unwrap_or(0)));
 
-        Command::new("wl-copy")
-            .execute_input(&output.stdout)
-            .expect("failed to copy ocr text to clipboard");
+        let monitors = focal_monitor();
+        let focused = monitors.focused().name;
+
+        let mut outputs = Vec::new();
+        let mut backgrounds = Vec::new();
+        for mon in monitors.all() {
+            let img = self.capture_image(&mon.name, "", self.cursor);
+            let (output, info) = self.save_image(
+                DynamicImage::ImageRgba8(img),
+                CaptureInfo::new("monitor").monitor(&mon),
+            );
+
+            let (_, background) = self.record(&output, &info);
+            backgrounds.push(background);
+            outputs.push((mon.name, output));
+        }
 
-        if self.notify
-            && let Ok(copied_text) = std::str::from_utf8(&output.stdout)
-        {
-            show_notification(copied_text, None);
+        let Some((_, copied)) = outputs
+            .iter()
+            .find(|(name, _)| *name == focused)
+            .or_else(|| outputs.first())
+        else {
+            eprintln!("No monitors were found.");
+            std::process::exit(1);
+        };
+
+        Clipboard::new()
+            .primary(self.primary)
+            .modes(&self.copy, copied, self.format.mime_type())
+            .copy();
+
+        if self.notify {
+            let dir = copied.parent().unwrap_or_else(|| Path::new("/"));
+            show_notification(
+                &format!(
+                    "{} screenshots captured to {}",
+                    outputs.len(),
+                    dir.display()
+                ),
+                Some(copied),
+            );
+        }
+
+        for background in backgrounds {
+            background.wait();
         }
     }
 
-    pub fn rofi(&mut self, theme: Option<&PathBuf>) {
-        let mut opts = vec!["󰒉\tSelection", "󰍹\tWindow", "󰍹\tMonitor"];
+    pub fn all(&self) {
+        if is_niri() || is_mango() {
+            self.all_composited();
+            return;
+        }
+
+        let geometry = focal_monitor().total_dimensions().to_string();
+
+        std::thread::sleep(std::time::Duration::from_secs(self.delay.unwrap_or(0)));
+        self.capture(
+            CaptureInfo {
+                geometry: geometry.clone(),
+                ..CaptureInfo::new("all")
+            },
+            "",
+            &geometry,
+        );
+    }
+
+    /// captures each monitor separately and places them according to the monitor layout
+    fn all_composited(&self) {
+        std::thread::sleep(std::time::Duration::from_secs(self.delay.unwrap_or(0)));
 
-        if !(is_niri() || is_mango()) {
-            opts.push("󰍺\tAll");
+        let monitors = focal_monitor();
+        let captures: Vec<_> = monitors
+            .all()
+            .into_iter()
+            .map(|mon| {
+                let img = Self::capture_scaled(&mon.name, "", None, self.cursor);
+                (mon, img)
+            })
+            .collect();
+
+        // monitors are captured at their own scale, so they are normalized to the highest scale
+        let max_scale = captures
+            .iter()
+            .map(|(mon, _)| f64::from(mon.scale))
+            .fold(1.0, f64::max);
+        let scale = if is_niri() {
+            // niri captures are scaled when processing, relative to the focused monitor
+            if self.logical && self.scale.is_none() {
+                f64::from(monitors.focused().scale)
+            } else {
+                max_scale
+            }
+        } else {
+            self.grim_scale().unwrap_or(max_scale)
+        };
+
+        let img = resize::composite(&captures, scale);
+        self.finish_image(DynamicImage::ImageRgba8(img), CaptureInfo::new("all"));
+    }
+
+    fn edit(&self, output: &Path) {
+        if let Some(editor) = &self.edit {
+            edit(editor, output);
         }
+    }
+
+    pub fn rofi(&mut self, theme: Option<&PathBuf>) {
+        let mut opts = vec!["󰒉\tSelection", "󰍹\tWindow", "󰍹\tMonitor", "󰍺\tAll"];
 
         // don't show "All" option if single monitor
         if focal_monitor().all().len() == 1 {
@@ -464,7 +1071,85 @@ impl Screenshot {
     }
 }
 
-pub fn main(args: ImageArgs) {
+/// exits on invalid combinations of arguments, returns if the screenshot is written to stdout
+fn validate_args(args: &mut ImageArgs) -> bool {
+    if args.edit.as_ref().is_some_and(String::is_empty) {
+        Cli::command()
+            .error(
+                clap::error::ErrorKind::MissingRequiredArgument,
+                "--edit requires a COMMAND if no editor is set in the config file.",
+            )
+            .exit()
+    }
+
+    let area = args.area_args.parse();
+    if args.scroll && !matches!(area, Some(CaptureArea::Selection)) {
+        Cli::command()
+            .error(
+                clap::error::ErrorKind::ArgumentConflict,
+                "--scroll can only be used with --area selection.",
+            )
+            .exit()
+    }
+
+    if matches!(area, Some(CaptureArea::EachMonitor))
+        && (args.interval_args.interval.is_some() || args.interval_args.burst)
+    {
+        Cli::command()
+            .error(
+                clap::error::ErrorKind::ArgumentConflict,
+                "--interval and --burst cannot be used with --area each-monitor.",
+            )
+            .exit()
+    }
+
+    // every monitor is saved separately, so there is no single capture to edit or scan
+    if matches!(area, Some(CaptureArea::EachMonitor))
+        && (args.edit.is_some() || args.ocr.is_some() || args.qr || args.decode)
+    {
+        Cli::command()
+            .error(
+                clap::error::ErrorKind::ArgumentConflict,
+                "--edit, --ocr, --qr and --decode cannot be used with --area each-monitor.",
+            )
+            .exit()
+    }
+
+    // FILE of - writes to stdout, so there is no file to notify about or copy
+    let stdout = args.filename.as_deref() == Some(Path::new("-"));
+    if stdout {
+        if args.edit.is_some()
+            || args.ocr.is_some()
+            || args.qr
+            || args.decode
+            || args.common_args.upload.is_some()
+            || args.interval_args.interval.is_some()
+            || args.interval_args.burst
+            || matches!(area, Some(CaptureArea::EachMonitor))
+        {
+            Cli::command()
+                .error(
+                    clap::error::ErrorKind::ArgumentConflict,
+                    "--edit, --ocr, --qr, --decode, --upload, --interval, --burst and each-monitor cannot be used when writing to stdout.",
+                )
+                .exit()
+        }
+
+        args.common_args.no_notify = true;
+        args.common_args.copy = vec![CopyMode::None];
+    }
+
+    stdout
+}
+
+pub fn main(mut args: ImageArgs) {
+    if args.interval_args.stop {
+        if IntervalLock::remove() {
+            println!("Stopping interval capture...");
+        }
+        return;
+    }
+
     if !cfg!(feature = "ocr") && args.ocr.is_some() {
         Cli::command()
             .error(
@@ -474,36 +1159,80 @@ pub fn main(args: ImageArgs) {
             .exit()
     }
 
+    let config = Config::load(args.common_args.profile.as_deref());
+    config.apply_image(&mut args);
+
+    let stdout = validate_args(&mut args);
+
     // check if all required programs are installed
     check_programs(&args.required_programs());
 
-    let fname = format!("{}.png", iso8601_filename());
-
-    let output = if args.common_args.no_save {
-        PathBuf::from(format!("/tmp/{fname}"))
+    let format = args.format.unwrap_or_default();
+
+    let template = FilenameTemplate::from_args(
+        &args.common_args,
+        args.filename,
+        &config.image_dir(),
+        format.extension(),
+    );
+
+    let interval = Interval::from_args(
+        &args.interval_args,
+        FilenameTemplate::from_args(&args.common_args, None, &config.video_dir(), "mp4"),
+    );
+    // multiple files are created, so they need different names
+    let template = if interval.is_some() {
+        template.with_field("n")
+    } else if matches!(args.area_args.parse(), Some(CaptureArea::EachMonitor)) {
+        template.with_field("monitor")
     } else {
-        create_parent_dirs(args.filename.unwrap_or_else(|| {
-            dirs::picture_dir()
-                .expect("could not get $XDG_PICTURES_DIR")
-                .join(format!("Screenshots/{fname}"))
-        }))
+        template
     };
 
     let mut screenshot = Screenshot {
-        output,
+        template,
         delay: args.common_args.delay,
         freeze: args.freeze,
-        edit: args.edit,
+        edit: args.edit.as_deref().map(|edit| {
+            Editor::new(edit).unwrap_or_else(|err| {
+                Cli::command()
+                    .error(clap::error::ErrorKind::InvalidValue, err.to_string())
+                    .exit()
+            })
+        }),
         no_rounded_windows: args.common_args.no_rounded_windows,
         icons: !args.rofi_args.no_icons,
         notify: !args.common_args.no_notify,
-        ocr: args.ocr,
+        ocr: args.ocr.map(|lang| Ocr::from_args(lang, args.ocr_args)),
+        barcode: if args.qr {
+            Some(Barcode::Qr)
+        } else {
+            args.decode.then_some(Barcode::Any)
+        },
+        format,
+        quality: args.quality,
+        scale: args.scale,
+        logical: args.logical,
+        max_width: args.max_width,
+        max_height: args.max_height,
+        cursor: args.common_args.show_cursor(false),
+        copy: args.common_args.copy_modes(CopyMode::Image),
+        primary: args.common_args.primary,
+        beautify: Beautify::from_args(&args.beautify_args),
+        hooks: Hooks::from_args(&args.common_args, &config, CaptureKind::Image),
+        upload: config.upload(args.common_args.upload.as_deref()),
+        stdout,
         slurp: args.common_args.slurp,
     };
 
     if args.rofi_args.rofi {
         screenshot.rofi(args.rofi_args.theme.as_ref());
     } else if let Some(area) = args.area_args.parse() {
+        if let Some(interval) = interval {
+            screenshot.repeat(&area, &interval);
+            return;
+        }
+
         match area {
             CaptureArea::Monitor => screenshot.monitor(),
             CaptureArea::Window => {
@@ -516,8 +1245,10 @@ pub fn main(args: ImageArgs) {
                     screenshot.selection();
                 }
             }
+            CaptureArea::Selection if args.scroll => screenshot.scroll(args.scroll_with),
             CaptureArea::Selection => screenshot.selection(),
             CaptureArea::All => screenshot.all(),
+            CaptureArea::EachMonitor => screenshot.each_monitor(),
         }
     }
 }
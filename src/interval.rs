@@ -0,0 +1,169 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    time::{Duration, Instant},
+};
+
+use execute::Execute;
+
+use crate::{cli::image::IntervalArgs, template::FilenameTemplate};
+
+/// number of captures for --burst if --count is not given
+const BURST_COUNT: u32 = 10;
+
+/// exists while capturing at an interval, removing it stops the capture
+pub struct IntervalLock;
+
+impl IntervalLock {
+    fn path() -> PathBuf {
+        dirs::runtime_dir()
+            .expect("could not get $XDG_RUNTIME_DIR")
+            .join("focal-interval.lock")
+    }
+
+    pub fn exists() -> bool {
+        Self::path().exists()
+    }
+
+    pub fn create() {
+        std::fs::write(Self::path(), std::process::id().to_string())
+            .expect("failed to write focal-interval.lock");
+    }
+
+    /// returns true if a capture was running
+    pub fn remove() -> bool {
+        std::fs::remove_file(Self::path()).is_ok()
+    }
+}
+
+pub struct Timelapse {
+    pub fps: u32,
+    pub template: FilenameTemplate,
+}
+
+/// repeated captures of the same area
+pub struct Interval {
+    /// zero for bursts, which are captured as fast as possible
+    pub every: Duration,
+    pub count: Option<u32>,
+    pub timelapse: Option<Timelapse>,
+}
+
+impl Interval {
+    pub fn from_args(args: &IntervalArgs, timelapse_template: FilenameTemplate) -> Option<Self> {
+        let every = if args.burst {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(args.interval?)
+        };
+
+        Some(Self {
+            every,
+            count: args.count.or_else(|| args.burst.then_some(BURST_COUNT)),
+            timelapse: args.timelapse.map(|fps| Timelapse {
+                fps,
+                template: timelapse_template,
+            }),
+        })
+    }
+
+    pub const fn is_burst(&self) -> bool {
+        self.every.is_zero()
+    }
+
+    /// waits until the nth capture is due, returning false if stopped in the meantime
+    pub fn wait(&self, start: Instant, n: u32) -> bool {
+        if self.count.is_some_and(|count| n >= count) {
+            return false;
+        }
+
+        let due = start + self.every * n;
+        while Instant::now() < due {
+            if !IntervalLock::exists() {
+                return false;
+            }
+            std::thread::sleep((due - Instant::now()).min(Duration::from_millis(100)));
+        }
+
+        IntervalLock::exists()
+    }
+}
+
+/// assembles the frames into a video using ffmpeg, returning false if it failed
+pub fn timelapse(frames: &[PathBuf], fps: u32, output: &Path) -> bool {
+    let Some(ext) = frames
+        .first()
+        .and_then(|frame| frame.extension())
+        .map(|ext| ext.to_string_lossy().to_string())
+    else {
+        return false;
+    };
+
+    // ffmpeg reads numbered sequences, so the frames are linked with sequential names
+    let dir = dirs::runtime_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join(format!("focal-timelapse-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("failed to create timelapse directory");
+    for (idx, frame) in frames.iter().enumerate() {
+        // relative links would be resolved from the timelapse directory
+        let frame = std::path::absolute(frame).expect("failed to get timelapse frame path");
+        std::os::unix::fs::symlink(frame, dir.join(format!("{idx:06}.{ext}")))
+            .expect("failed to link timelapse frame");
+    }
+
+    let success = Command::new("ffmpeg")
+        .arg("-y")
+        .arg("-loglevel")
+        .arg("error")
+        .arg("-framerate")
+        .arg(fps.to_string())
+        .arg("-i")
+        .arg(dir.join(format!("%06d.{ext}")))
+        // h264 requires the width and height to be even
+        .arg("-vf")
+        .arg("pad=ceil(iw/2)*2:ceil(ih/2)*2,format=yuv420p")
+        .arg("-c:v")
+        .arg("libx264")
+        .arg(output)
+        .execute()
+        .is_ok_and(|status| status == Some(0));
+
+    std::fs::remove_dir_all(&dir).ok();
+    success
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(interval: Option<f64>, burst: bool, count: Option<u32>) -> IntervalArgs {
+        IntervalArgs {
+            interval,
+            burst,
+            count,
+            timelapse: None,
+            stop: false,
+        }
+    }
+
+    #[test]
+    fn test_from_args() {
+        let template = || FilenameTemplate::new("/tmp/{n}.mp4");
+
+        assert!(Interval::from_args(&args(None, false, None), template()).is_none());
+
+        let interval = Interval::from_args(&args(Some(1.5), false, None), template())
+            .expect("interval should be set");
+        assert_eq!(interval.every, Duration::from_millis(1500));
+        assert_eq!(interval.count, None, "intervals should run until stopped");
+
+        let burst =
+            Interval::from_args(&args(None, true, None), template()).expect("burst should be set");
+        assert!(burst.is_burst());
+        assert_eq!(burst.count, Some(BURST_COUNT));
+        assert!(
+            !burst.wait(Instant::now(), BURST_COUNT),
+            "should stop once the count is reached"
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use std::process::Command;
+
+use clap::CommandFactory;
+use execute::Execute;
+
+use crate::{
+    check_programs,
+    cli::{
+        focal::{Cli, CopyMode},
+        image::OutputFormat,
+        last::{LastAction, LastArgs},
+    },
+    clipboard::Clipboard,
+    config::Config,
+    editor::Editor,
+    history::{Entry, History},
+    hooks::CaptureKind,
+    image,
+    ocr::Ocr,
+};
+
+fn error(kind: clap::error::ErrorKind, msg: &str) -> ! {
+    Cli::command().error(kind, msg).exit()
+}
+
+/// copies the capture the same way as after capturing
+fn copy(entry: &Entry, primary: bool) {
+    let (mode, mime) = match entry.kind {
+        CaptureKind::Image => (
+            CopyMode::Image,
+            OutputFormat::from_path(&entry.path)
+                .unwrap_or_default()
+                .mime_type(),
+        ),
+        CaptureKind::Video => (CopyMode::Uri, "video/mp4"),
+    };
+
+    Clipboard::new()
+        .primary(primary)
+        .modes(&[mode], &entry.path, mime)
+        .copy();
+}
+
+/// options used when performing an action on a capture
+pub struct Actions {
+    pub editor: Option<String>,
+    pub ocr: Ocr,
+    pub notify: bool,
+    pub primary: bool,
+}
+
+impl Actions {
+    pub fn perform(&self, entry: &Entry, action: LastAction) {
+        if matches!(action, LastAction::Edit | LastAction::Ocr) && entry.kind != CaptureKind::Image
+        {
+            error(
+                clap::error::ErrorKind::InvalidValue,
+                "Only screenshots can be edited or used for OCR, use --kind image.",
+            );
+        }
+
+        match action {
+            LastAction::Path => println!("{}", entry.path.display()),
+            LastAction::Open => {
+                Command::new("xdg-open")
+                    .arg(&entry.path)
+                    .execute()
+                    .expect("Failed to open file");
+            }
+            LastAction::Copy => copy(entry, self.primary),
+            LastAction::CopyPath => {
+                Clipboard::new()
+                    .primary(self.primary)
+                    .text(&entry.path.to_string_lossy())
+                    .copy();
+            }
+            LastAction::Edit => {
+                let Some(editor) = &self.editor else {
+                    error(
+                        clap::error::ErrorKind::MissingRequiredArgument,
+                        "edit requires --editor if no editor is set in the config file.",
+                    );
+                };
+                let editor = Editor::new(editor).unwrap_or_else(|err| {
+                    error(clap::error::ErrorKind::InvalidValue, &err.to_string())
+                });
+
+                image::edit(&editor, &entry.path);
+                copy(entry, self.primary);
+            }
+            LastAction::Ocr => self.ocr.run(&entry.path, self.primary, self.notify),
+            LastAction::Delete => {
+                std::fs::remove_file(&entry.path).expect("failed to delete capture");
+                println!("Deleted {}", entry.path.display());
+            }
+        }
+    }
+}
+
+pub fn main(args: LastArgs) {
+    if !cfg!(feature = "ocr") && args.action == LastAction::Ocr {
+        error(
+            clap::error::ErrorKind::InvalidValue,
+            "OCR support was not built in this version of focal.",
+        );
+    }
+
+    let config = Config::load(args.profile.as_deref());
+
+    match args.action {
+        LastAction::Open => check_programs(&[]),
+        LastAction::Ocr => check_programs(&["tesseract"]),
+        _ => {}
+    }
+
+    let Some(entry) = History::default()
+        .entries()
+        .expect("failed to read capture history")
+        .into_iter()
+        .rev()
+        .find(|entry| args.kind.is_none_or(|kind| entry.is_kind(kind)))
+    else {
+        eprintln!("No captures found in history.");
+        std::process::exit(1);
+    };
+
+    Actions {
+        ocr: config.ocr(args.ocr_lang),
+        editor: args.editor.or(config.image.editor),
+        // --notify turns notifications back on when disabled in the config
+        notify: args.notify || !(args.no_notify || config.no_notify.unwrap_or_default()),
+        primary: config.primary.unwrap_or_default(),
+    }
+    .perform(&entry, args.action);
+}
@@ -1,15 +1,38 @@
-use std::{path::PathBuf, process::Command};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
 
 mod hyprland;
 mod mango;
 mod niri;
 mod sway;
 
+mod barcode;
+mod beautify;
 pub mod cli;
+pub mod clipboard;
+pub mod color;
+pub mod config;
+mod editor;
+mod encode;
+mod freeze;
+pub mod history;
+pub mod hooks;
 pub mod image;
+mod interval;
+pub mod last;
 mod monitor;
+mod ocr;
+pub mod process;
+pub mod recent;
+mod resize;
 pub mod rofi;
+mod screencopy;
+mod scroll;
 mod slurp;
+pub mod template;
+pub mod upload;
 pub mod video;
 mod wf_recorder;
 
@@ -20,6 +43,7 @@ pub use video::Screencast;
 
 use crate::monitor::FocalMonitors;
 
+/// creates the parent directories of path, appending a counter to the filename if it already exists
 pub fn create_parent_dirs(path: PathBuf) -> PathBuf {
     if let Some(parent) = path.parent()
         && !parent.exists()
@@ -27,11 +51,32 @@ pub fn create_parent_dirs(path: PathBuf) -> PathBuf {
         std::fs::create_dir_all(parent).expect("failed to create parent directories");
     }
 
-    path
+    if !path.exists() {
+        return path;
+    }
+
+    let stem = path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let ext = path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+
+    (1..u32::MAX)
+        .map(|n| path.with_file_name(format!("{stem}-{n}{ext}")))
+        .find(|path| !path.exists())
+        .expect("could not find an unused filename")
 }
 
-pub fn iso8601_filename() -> String {
-    chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+/// moves a file, falling back to copying if the destination is on a different filesystem
+pub fn move_file(from: &Path, to: &Path) {
+    if std::fs::rename(from, to).is_err() {
+        std::fs::copy(from, to).expect("failed to copy file");
+        std::fs::remove_file(from).expect("failed to remove file");
+    }
 }
 
 pub fn command_json<T: serde::de::DeserializeOwned>(cmd: &mut Command) -> T {
@@ -73,7 +118,7 @@ pub fn show_notification(body: &str, output: Option<&PathBuf>) {
 
 /// check if all required programs are installed
 pub fn check_programs(progs: &[&str]) {
-    let mut all_progs = std::collections::HashSet::from(["wl-copy", "xdg-open"]);
+    let mut all_progs = std::collections::HashSet::from(["xdg-open"]);
 
     all_progs.extend(progs);
 
@@ -8,7 +8,13 @@ fn main() {
         FocalSubcommand::Generate(args) => {
             generate_completions("focal", &mut Cli::command(), &args.shell);
         }
+        FocalSubcommand::ClipboardServe => focal::clipboard::Clipboard::serve(),
         FocalSubcommand::Image(image_args) => focal::image::main(image_args),
+        FocalSubcommand::Color(color_args) => focal::color::main(color_args),
+        FocalSubcommand::History(history_args) => focal::history::main(&history_args),
+        FocalSubcommand::Last(last_args) => focal::last::main(last_args),
+        FocalSubcommand::Process(process_args) => focal::process::main(process_args),
+        FocalSubcommand::Recent(recent_args) => focal::recent::main(recent_args),
         #[cfg(feature = "video")]
         FocalSubcommand::Video(video_args) => focal::video::main(video_args),
     }
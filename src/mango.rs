@@ -1,6 +1,6 @@
 use crate::{
     SlurpGeom, command_json,
-    monitor::{FocalMonitor, FocalMonitors, Rotation},
+    monitor::{FocalMonitor, FocalMonitors, FocalWindow, Rotation, logical_size},
 };
 use std::process::{Command, Stdio};
 
@@ -45,24 +45,27 @@ fn to_focal_monitor(mon: &WlrMonitor) -> FocalMonitor {
             std::process::exit(1);
         });
 
+    let rotation = match mon.transform.as_str() {
+        "normal" => Rotation::Normal,
+        "90" => Rotation::Normal90,
+        "180" => Rotation::Normal180,
+        "270" => Rotation::Normal270,
+        "flipped" => Rotation::Flipped,
+        "flipped-90" => Rotation::Flipped90,
+        "flipped-180" => Rotation::Flipped180,
+        "flipped-270" => Rotation::Flipped270,
+        _ => unimplemented!("Invalid monitor transform"),
+    };
+    let (w, h) = logical_size(mode.width, mode.height, mon.scale, &rotation);
+
     FocalMonitor {
         name: mon.name.clone(),
         x: mon.position.x,
         y: mon.position.y,
-        w: mode.width,
-        h: mode.height,
+        w,
+        h,
         scale: mon.scale,
-        rotation: match mon.transform.as_str() {
-            "normal" => Rotation::Normal,
-            "90" => Rotation::Normal90,
-            "180" => Rotation::Normal180,
-            "270" => Rotation::Normal270,
-            "flipped" => Rotation::Flipped,
-            "flipped-90" => Rotation::Flipped90,
-            "flipped-180" => Rotation::Flipped180,
-            "flipped-270" => Rotation::Flipped270,
-            _ => unimplemented!("Invalid monitor transform"),
-        },
+        rotation,
     }
 }
 
@@ -107,9 +110,38 @@ impl FocalMonitors for MangoMonitors {
             .clone()
     }
 
-    fn window_geoms(&self) -> Vec<SlurpGeom> {
+    fn windows(&self) -> Vec<(SlurpGeom, FocalWindow)> {
         // TODO: mango currently doesn't expose window geometries, see:
         // https://github.com/DreamMaoMao/mangowc/issues/418
         Vec::new()
     }
+
+    fn active_window(&self) -> Option<FocalWindow> {
+        let mon_name = self.focused().name;
+
+        let output = Command::new("mmsg")
+            .arg("-g")
+            .stdout(Stdio::piped())
+            .output()
+            .expect("Failed to execute mmsg -g");
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        // lines are in the form of "<monitor> <key> <value>"
+        let mut window = FocalWindow::default();
+        for line in stdout.lines() {
+            let parts: Vec<_> = line.splitn(3, ' ').collect();
+            if parts.len() != 3 || parts[0] != mon_name {
+                continue;
+            }
+
+            match parts[1] {
+                "appid" => window.class = parts[2].to_string(),
+                "title" => window.title = parts[2].to_string(),
+                _ => {}
+            }
+        }
+
+        Some(window)
+    }
 }
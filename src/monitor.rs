@@ -33,8 +33,30 @@ impl Rotation {
         })
         .to_string()
     }
+
+    /// if the width and height are swapped by the rotation
+    pub const fn is_transposed(&self) -> bool {
+        matches!(
+            self,
+            Self::Normal90 | Self::Normal270 | Self::Flipped90 | Self::Flipped270
+        )
+    }
+}
+
+/// logical size of a monitor from the resolution of its mode, accounting for scale and rotation
+pub fn logical_size(w: i32, h: i32, scale: f32, rotation: &Rotation) -> (i32, i32) {
+    #[allow(clippy::cast_possible_truncation)]
+    let scaled = |n: i32| (f64::from(n) / f64::from(scale)).round() as i32;
+    let (w, h) = (scaled(w), scaled(h));
+
+    if rotation.is_transposed() {
+        (h, w)
+    } else {
+        (w, h)
+    }
 }
 
+/// the position and size are in logical pixels, as used by slurp and grim
 #[allow(clippy::module_name_repetitions)]
 #[derive(Debug, Clone)]
 pub struct FocalMonitor {
@@ -47,6 +69,23 @@ pub struct FocalMonitor {
     pub rotation: Rotation,
 }
 
+impl FocalMonitor {
+    pub const fn geom(&self) -> SlurpGeom {
+        SlurpGeom {
+            w: self.w,
+            h: self.h,
+            x: self.x,
+            y: self.y,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct FocalWindow {
+    pub class: String,
+    pub title: String,
+}
+
 pub trait FocalMonitors {
     /// returns a vector of all monitors
     fn all(&self) -> Vec<FocalMonitor>;
@@ -55,17 +94,118 @@ pub trait FocalMonitors {
     fn focused(&self) -> FocalMonitor;
 
     /// returns geometries of all visible (active) windows across all monitors
-    fn window_geoms(&self) -> Vec<SlurpGeom>;
-
-    /// total dimensions across all monitors
-    fn total_dimensions(&self) -> (i32, i32) {
-        let mut w = 0;
-        let mut h = 0;
-        for mon in self.all() {
-            w = w.max(mon.x + mon.w);
-            h = h.max(mon.y + mon.h);
+    fn windows(&self) -> Vec<(SlurpGeom, FocalWindow)>;
+
+    /// returns the class and title of the focused window
+    fn active_window(&self) -> Option<FocalWindow>;
+
+    /// bounding box of all monitors, which may start at negative coordinates
+    fn total_dimensions(&self) -> SlurpGeom {
+        self.all()
+            .iter()
+            .map(FocalMonitor::geom)
+            .reduce(|a, b| a.union(&b))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Layout(Vec<FocalMonitor>);
+
+    impl FocalMonitors for Layout {
+        fn all(&self) -> Vec<FocalMonitor> {
+            self.0.clone()
         }
 
-        (w, h)
+        fn focused(&self) -> FocalMonitor {
+            self.0[0].clone()
+        }
+
+        fn windows(&self) -> Vec<(SlurpGeom, FocalWindow)> {
+            Vec::new()
+        }
+
+        fn active_window(&self) -> Option<FocalWindow> {
+            None
+        }
+    }
+
+    /// monitor with the resolution of its mode
+    fn monitor(geom: &str, scale: f32, rotation: Rotation) -> FocalMonitor {
+        let SlurpGeom { x, y, w, h } = geom.parse().expect("valid geometry");
+        let (w, h) = logical_size(w, h, scale, &rotation);
+
+        FocalMonitor {
+            name: geom.to_string(),
+            x,
+            y,
+            w,
+            h,
+            scale,
+            rotation,
+        }
+    }
+
+    fn total(monitors: Vec<FocalMonitor>) -> String {
+        Layout(monitors).total_dimensions().to_string()
+    }
+
+    #[test]
+    fn test_logical_size() {
+        assert_eq!(
+            logical_size(1920, 1080, 1.0, &Rotation::Flipped),
+            (1920, 1080)
+        );
+        assert_eq!(
+            logical_size(3840, 2160, 2.0, &Rotation::Normal90),
+            (1080, 1920)
+        );
+        assert_eq!(
+            logical_size(2560, 1440, 1.5, &Rotation::Normal),
+            (1707, 960)
+        );
+    }
+
+    #[test]
+    fn test_total_dimensions() {
+        assert_eq!(
+            total(vec![
+                monitor("0,0 1920x1080", 1.0, Rotation::Normal),
+                monitor("1920,0 1920x1080", 1.0, Rotation::Normal),
+            ]),
+            "0,0 3840x1080"
+        );
+
+        assert_eq!(
+            total(vec![
+                monitor("0,0 1920x1080", 1.0, Rotation::Normal),
+                monitor("-2560,-360 2560x1440", 1.0, Rotation::Normal),
+            ]),
+            "-2560,-360 4480x1440",
+            "monitors at negative coordinates should not be cropped"
+        );
+
+        assert_eq!(
+            total(vec![
+                monitor("0,0 1920x1080", 1.0, Rotation::Normal),
+                monitor("2000,500 1280x720", 1.0, Rotation::Normal),
+            ]),
+            "0,0 3280x1220",
+            "gaps between monitors should be included"
+        );
+
+        assert_eq!(
+            total(vec![
+                monitor("-1080,0 3840x2160", 2.0, Rotation::Normal270),
+                monitor("0,400 3840x2160", 1.5, Rotation::Normal),
+            ]),
+            "-1080,0 3640x1920",
+            "scaled and rotated monitors should use their logical size"
+        );
+
+        assert_eq!(total(Vec::new()), "0,0 0x0");
     }
 }
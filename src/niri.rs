@@ -2,7 +2,7 @@ use niri_ipc::{Output, Request, Response, Transform, socket::Socket};
 
 use crate::{
     SlurpGeom,
-    monitor::{FocalMonitor, FocalMonitors, Rotation},
+    monitor::{FocalMonitor, FocalMonitors, FocalWindow, Rotation},
 };
 
 #[allow(clippy::module_name_repetitions)]
@@ -45,8 +45,8 @@ impl FocalMonitors for NiriMonitors {
         };
 
         monitors
-            .iter()
-            .filter_map(|(_, mon)| mon.logical.map(|_| mon))
+            .values()
+            .filter_map(|mon| mon.logical.map(|_| mon))
             .map(to_focal_monitor)
             .collect()
     }
@@ -54,10 +54,10 @@ impl FocalMonitors for NiriMonitors {
     fn focused(&self) -> FocalMonitor {
         let Ok(Response::FocusedOutput(Some(monitor))) = Socket::connect()
             .expect("failed to connect to niri socket")
-            .send(Request::Outputs)
-            .expect("failed to send Outputs request to niri")
+            .send(Request::FocusedOutput)
+            .expect("failed to send FocusedOutput request to niri")
         else {
-            panic!("unexpected response from niri, should be FocusedOutputs");
+            panic!("unexpected response from niri, should be FocusedOutput");
         };
 
         assert!(
@@ -69,8 +69,23 @@ impl FocalMonitors for NiriMonitors {
         to_focal_monitor(&monitor)
     }
 
-    fn window_geoms(&self) -> Vec<SlurpGeom> {
+    fn windows(&self) -> Vec<(SlurpGeom, FocalWindow)> {
         // TODO: niri currently doesn't expose window geometries
         Vec::new()
     }
+
+    fn active_window(&self) -> Option<FocalWindow> {
+        let Ok(Response::FocusedWindow(window)) = Socket::connect()
+            .expect("failed to connect to niri socket")
+            .send(Request::FocusedWindow)
+            .expect("failed to send FocusedWindow request to niri")
+        else {
+            panic!("unexpected response from niri, should be FocusedWindow");
+        };
+
+        window.map(|win| FocalWindow {
+            class: win.app_id.unwrap_or_default(),
+            title: win.title.unwrap_or_default(),
+        })
+    }
 }
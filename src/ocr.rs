@@ -0,0 +1,488 @@
+use std::{
+    fmt,
+    io::Cursor,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use execute::Execute;
+use image::{DynamicImage, GrayImage, imageops::FilterType};
+
+use crate::{
+    cli::image::{OcrArgs, OcrCleanup, OcrFormat},
+    clipboard::Clipboard,
+    show_notification,
+};
+
+/// largest dimension of the image after upscaling for preprocessing
+const MAX_PREPROCESS_SIZE: u32 = 8000;
+
+#[derive(Debug)]
+pub struct OcrError {
+    message: String,
+}
+
+impl OcrError {
+    fn new(msg: &str) -> Self {
+        Self {
+            message: msg.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// options for recognizing text with tesseract
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Ocr {
+    /// languages separated by +, e.g. eng+deu
+    pub lang: Option<String>,
+    pub psm: Option<u8>,
+    pub format: OcrFormat,
+    pub cleanup: Vec<OcrCleanup>,
+    pub preprocess: bool,
+}
+
+impl Ocr {
+    /// an empty language uses the tesseract default
+    pub fn from_args(lang: String, args: OcrArgs) -> Self {
+        Self {
+            lang: (!lang.is_empty()).then_some(lang),
+            psm: args.ocr_psm,
+            format: args.ocr_format.unwrap_or_default(),
+            cleanup: args.ocr_cleanup,
+            preprocess: args.ocr_preprocess,
+        }
+    }
+
+    fn command(&self, input: &Path) -> Command {
+        let mut cmd = Command::new("tesseract");
+        cmd.arg(input).arg("-");
+
+        if let Some(lang) = &self.lang
+            && !lang.is_empty()
+        {
+            // eng,deu is also accepted for multiple languages
+            cmd.arg("-l").arg(lang.replace(',', "+"));
+        }
+
+        if let Some(psm) = self.psm {
+            cmd.arg("--psm").arg(psm.to_string());
+        }
+
+        match self.format {
+            OcrFormat::Text => {}
+            OcrFormat::Hocr => {
+                cmd.arg("hocr");
+            }
+            // markdown is created from the layout in the tsv output
+            OcrFormat::Tsv | OcrFormat::Markdown => {
+                cmd.arg("tsv");
+            }
+        }
+
+        cmd
+    }
+
+    /// runs tesseract on the image, returning the output in the requested format
+    pub fn recognize(&self, image: &Path) -> Result<String, OcrError> {
+        let output = if self.preprocess {
+            let img = image::open(image)
+                .map_err(|err| OcrError::new(&format!("Failed to read image for OCR: {err}")))?;
+
+            let mut png = Vec::new();
+            DynamicImage::ImageLuma8(preprocess(&img))
+                .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+                .map_err(|err| OcrError::new(&format!("Failed to preprocess image: {err}")))?;
+
+            self.command(Path::new("-"))
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .execute_input_output(&png)
+        } else {
+            self.command(image)
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .execute_output()
+        }
+        .map_err(|err| OcrError::new(&format!("Failed to run tesseract: {err}")))?;
+
+        if !output.status.success() {
+            return Err(OcrError::new(&format!(
+                "tesseract failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(match self.format {
+            OcrFormat::Text => clean(&stdout, &self.cleanup),
+            OcrFormat::Hocr | OcrFormat::Tsv => stdout.to_string(),
+            OcrFormat::Markdown => markdown(&stdout, &self.cleanup),
+        })
+    }
+
+    /// copies the text in the image to the clipboard
+    pub fn run(&self, image: &Path, primary: bool, notify: bool) {
+        match self.recognize(image) {
+            Ok(text) => {
+                Clipboard::new().primary(primary).text(&text).copy();
+
+                if notify {
+                    show_notification(&text, None);
+                }
+            }
+            Err(err) => {
+                eprintln!("{err}");
+                if notify {
+                    show_notification(&err.to_string(), None);
+                }
+            }
+        }
+    }
+}
+
+/// otsu's threshold, which best separates the pixels into foreground and background
+fn otsu(img: &GrayImage) -> u8 {
+    let mut histogram = [0u64; 256];
+    for pixel in img.pixels() {
+        histogram[usize::from(pixel.0[0])] += 1;
+    }
+
+    let total: u64 = histogram.iter().sum();
+    let sum: u64 = histogram
+        .iter()
+        .zip(0u64..)
+        .map(|(count, value)| count * value)
+        .sum();
+
+    let mut best = (0u8, 0.0);
+    let (mut bg_weight, mut bg_sum) = (0u64, 0u64);
+    for (threshold, (count, value)) in (0u8..=255).zip(histogram.iter().zip(0u64..)) {
+        bg_weight += count;
+        bg_sum += count * value;
+
+        let fg_weight = total - bg_weight;
+        if bg_weight == 0 || fg_weight == 0 {
+            continue;
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let variance = {
+            let bg_mean = bg_sum as f64 / bg_weight as f64;
+            let fg_mean = (sum - bg_sum) as f64 / fg_weight as f64;
+            bg_weight as f64 * fg_weight as f64 * (bg_mean - fg_mean).powi(2)
+        };
+
+        if variance > best.1 {
+            best = (threshold, variance);
+        }
+    }
+
+    best.0
+}
+
+/// converts to black text on a white background, upscaling so small text can be recognized
+pub fn preprocess(img: &DynamicImage) -> GrayImage {
+    let gray = img.to_luma8();
+
+    let factor = (MAX_PREPROCESS_SIZE / gray.width().max(gray.height()).max(1)).clamp(1, 3);
+    let gray = image::imageops::resize(
+        &gray,
+        gray.width() * factor,
+        gray.height() * factor,
+        FilterType::CatmullRom,
+    );
+
+    let threshold = otsu(&gray);
+
+    // tesseract expects dark text, so invert when most pixels are dark, e.g. dark themes
+    let dark = gray.pixels().filter(|p| p.0[0] <= threshold).count();
+    let invert = dark * 2 > gray.pixels().len();
+
+    GrayImage::from_fn(gray.width(), gray.height(), |x, y| {
+        let light = gray.get_pixel(x, y).0[0] > threshold;
+        image::Luma([if light == invert { 0 } else { 255 }])
+    })
+}
+
+/// joins words that were hyphenated at the end of a line
+fn dehyphenate(text: &str) -> String {
+    let re = regex::Regex::new(r"(\w)-\n[ \t]*(\p{Ll})").expect("invalid dehyphenate regex");
+    re.replace_all(text, "$1$2").to_string()
+}
+
+/// joins the lines within each paragraph, paragraphs are separated by blank lines
+fn join_lines(text: &str) -> String {
+    let re = regex::Regex::new(r"\n[ \t]*\n\s*").expect("invalid paragraph regex");
+
+    re.split(text)
+        .map(|paragraph| {
+            paragraph
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// collapses repeated spaces and blank lines
+fn normalize_whitespace(text: &str) -> String {
+    let spaces = regex::Regex::new(r"[ \t\u{a0}]+").expect("invalid whitespace regex");
+    let blank = regex::Regex::new(r"\n{3,}").expect("invalid blank line regex");
+
+    let lines: Vec<_> = text
+        .lines()
+        .map(|line| spaces.replace_all(line, " ").trim().to_string())
+        .collect();
+
+    blank
+        .replace_all(&lines.join("\n"), "\n\n")
+        .trim()
+        .to_string()
+}
+
+/// applies the cleanups in a fixed order, so hyphenated words are joined before lines are
+fn clean(text: &str, cleanup: &[OcrCleanup]) -> String {
+    let mut text = text.to_string();
+
+    if cleanup.contains(&OcrCleanup::Dehyphenate) {
+        text = dehyphenate(&text);
+    }
+    if cleanup.contains(&OcrCleanup::JoinLines) {
+        text = join_lines(&text);
+    }
+    if cleanup.contains(&OcrCleanup::Whitespace) {
+        text = normalize_whitespace(&text);
+    }
+
+    text
+}
+
+/// a recognized word from the tsv output
+struct Word {
+    /// page, block and paragraph the word is in
+    paragraph: (u32, u32, u32),
+    line: u32,
+    height: u32,
+    text: String,
+}
+
+fn parse_tsv(tsv: &str) -> Vec<Word> {
+    tsv.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let cols: Vec<_> = line.split('\t').collect();
+            let num = |idx: usize| cols.get(idx).and_then(|col| col.parse::<u32>().ok());
+
+            // level 5 rows are words
+            if cols.len() < 12 || num(0)? != 5 || cols[11].trim().is_empty() {
+                return None;
+            }
+
+            Some(Word {
+                paragraph: (num(1)?, num(2)?, num(3)?),
+                line: num(4)?,
+                height: num(9)?,
+                text: cols[11].trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+fn is_bullet(line: &str) -> bool {
+    ["•", "·", "-", "*", "–", "▪"].iter().any(|bullet| {
+        line.strip_prefix(bullet)
+            .is_some_and(|rest| rest.starts_with(' '))
+    })
+}
+
+/// formats a paragraph as a heading, list or plain paragraph based on the layout
+fn markdown_paragraph(
+    lines: &[String],
+    height: u32,
+    median: u32,
+    cleanup: &[OcrCleanup],
+) -> String {
+    if lines.iter().any(|line| is_bullet(line)) {
+        let mut items: Vec<String> = Vec::new();
+        for line in lines {
+            match line.split_once(' ') {
+                Some((_, rest)) if is_bullet(line) => items.push(rest.to_string()),
+                // continuation of the previous item
+                _ => match items.last_mut() {
+                    Some(item) => *item = clean(&format!("{item}\n{line}"), cleanup),
+                    None => items.push(line.clone()),
+                },
+            }
+        }
+
+        return items
+            .iter()
+            .map(|item| format!("- {}", clean(item, cleanup).replace('\n', " ")))
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+
+    let text = clean(&lines.join("\n"), cleanup);
+
+    // short paragraphs with large text are headings
+    if lines.len() <= 2 {
+        if height * 10 >= median * 18 {
+            return format!("# {}", text.replace('\n', " "));
+        }
+        if height * 10 >= median * 13 {
+            return format!("## {}", text.replace('\n', " "));
+        }
+    }
+
+    text
+}
+
+/// creates markdown from the tsv output, detecting headings and lists from the layout
+fn markdown(tsv: &str, cleanup: &[OcrCleanup]) -> String {
+    let words = parse_tsv(tsv);
+
+    let mut heights: Vec<_> = words.iter().map(|word| word.height).collect();
+    heights.sort_unstable();
+    let median = heights.get(heights.len() / 2).copied().unwrap_or_default();
+
+    // group the words into paragraphs of lines, keeping the reading order
+    let mut paragraphs: Vec<(Vec<String>, Vec<u32>)> = Vec::new();
+    let mut prev: Option<((u32, u32, u32), u32)> = None;
+    for word in &words {
+        let same_paragraph = prev.is_some_and(|(paragraph, _)| paragraph == word.paragraph);
+        let same_line = prev == Some((word.paragraph, word.line));
+
+        if !same_paragraph {
+            paragraphs.push((Vec::new(), Vec::new()));
+        }
+        let (lines, heights) = paragraphs.last_mut().expect("paragraph was just added");
+        if same_line {
+            let line = lines.last_mut().expect("line was already added");
+            line.push(' ');
+            line.push_str(&word.text);
+        } else {
+            lines.push(word.text.clone());
+        }
+        heights.push(word.height);
+
+        prev = Some((word.paragraph, word.line));
+    }
+
+    paragraphs
+        .iter()
+        .map(|(lines, heights)| {
+            let mut heights = heights.clone();
+            heights.sort_unstable();
+            let height = heights[heights.len() / 2];
+            markdown_paragraph(lines, height, median, cleanup)
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean() {
+        let text = "The quick brown  fox jumps over the lazy dog. A hyphen-\nated word\n\n\n\nNew   paragraph\n";
+
+        assert_eq!(clean(text, &[]), text);
+        assert_eq!(
+            clean(text, &[OcrCleanup::Dehyphenate]),
+            "The quick brown  fox jumps over the lazy dog. A hyphenated word\n\n\n\nNew   paragraph\n"
+        );
+        assert_eq!(
+            clean(text, &[OcrCleanup::JoinLines]),
+            "The quick brown  fox jumps over the lazy dog. A hyphen- ated word\n\nNew   paragraph"
+        );
+        assert_eq!(
+            clean(
+                text,
+                &[
+                    OcrCleanup::Whitespace,
+                    OcrCleanup::JoinLines,
+                    OcrCleanup::Dehyphenate
+                ]
+            ),
+            "The quick brown fox jumps over the lazy dog. A hyphenated word\n\nNew paragraph"
+        );
+        assert_eq!(
+            dehyphenate("exam-\nple well-\nKnown"),
+            "example well-\nKnown",
+            "only words continuing in lowercase are joined"
+        );
+    }
+
+    fn tsv_word(paragraph: u32, line: u32, height: u32, text: &str) -> String {
+        format!("5\t1\t1\t{paragraph}\t{line}\t1\t0\t0\t10\t{height}\t95\t{text}")
+    }
+
+    #[test]
+    fn test_markdown() {
+        let mut rows = vec![
+            "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext"
+                .to_string(),
+            // non word rows are ignored
+            "4\t1\t1\t1\t1\t0\t0\t0\t100\t20\t-1\t".to_string(),
+        ];
+        rows.extend([
+            tsv_word(1, 1, 24, "Release"),
+            tsv_word(1, 1, 24, "notes"),
+            tsv_word(2, 1, 12, "Focal"),
+            tsv_word(2, 1, 12, "now"),
+            tsv_word(2, 1, 12, "decodes"),
+            tsv_word(2, 2, 12, "QR"),
+            tsv_word(2, 2, 12, "codes."),
+            tsv_word(3, 1, 12, "•"),
+            tsv_word(3, 1, 12, "OCR"),
+            tsv_word(3, 2, 12, "•"),
+            tsv_word(3, 2, 12, "Color"),
+            tsv_word(3, 3, 12, "picker"),
+        ]);
+        let tsv = rows.join("\n");
+
+        assert_eq!(
+            markdown(&tsv, &[OcrCleanup::JoinLines]),
+            "# Release notes\n\nFocal now decodes QR codes.\n\n- OCR\n- Color picker"
+        );
+        assert_eq!(
+            markdown(&tsv, &[]),
+            "# Release notes\n\nFocal now decodes\nQR codes.\n\n- OCR\n- Color picker"
+        );
+    }
+
+    #[test]
+    fn test_preprocess() {
+        // light text on a dark background, as in a dark theme
+        let img = GrayImage::from_fn(40, 20, |x, y| {
+            image::Luma([if (10..20).contains(&x) && (5..15).contains(&y) {
+                200
+            } else {
+                30
+            }])
+        });
+        let threshold = otsu(&img);
+        assert!((30..200).contains(&threshold));
+
+        let processed = preprocess(&DynamicImage::ImageLuma8(img));
+        assert_eq!(
+            processed.dimensions(),
+            (120, 60),
+            "small images are upscaled"
+        );
+        assert_eq!(processed.get_pixel(0, 0).0[0], 255, "background is white");
+        assert_eq!(processed.get_pixel(45, 30).0[0], 0, "text is black");
+    }
+}
@@ -0,0 +1,115 @@
+use clap::CommandFactory;
+
+use crate::{
+    barcode::Barcode,
+    beautify::Beautify,
+    check_programs,
+    cli::{
+        focal::{Cli, CopyMode, copy_modes},
+        process::ProcessArgs,
+    },
+    clipboard,
+    config::Config,
+    editor::Editor,
+    hooks::Hooks,
+    image::Screenshot,
+    ocr::Ocr,
+    template::{CaptureInfo, FilenameTemplate},
+};
+
+fn error(kind: clap::error::ErrorKind, msg: &str) -> ! {
+    Cli::command().error(kind, msg).exit()
+}
+
+/// the active window is unrelated to an existing image, so only the area is set
+fn info(area: &str) -> CaptureInfo {
+    CaptureInfo {
+        area: area.to_string(),
+        ..Default::default()
+    }
+}
+
+pub fn main(mut args: ProcessArgs) {
+    if !cfg!(feature = "ocr") && args.ocr.is_some() {
+        error(
+            clap::error::ErrorKind::UnknownArgument,
+            "OCR support was not built in this version of focal.",
+        );
+    }
+
+    let config = Config::load(args.profile.as_deref());
+    config.apply_process(&mut args);
+
+    if args.edit.as_ref().is_some_and(String::is_empty) {
+        error(
+            clap::error::ErrorKind::MissingRequiredArgument,
+            "--edit requires a COMMAND if no editor is set in the config file.",
+        );
+    }
+
+    check_programs(&args.required_programs());
+
+    let (img, info) = args.file.as_deref().map_or_else(
+        || {
+            let img = clipboard::paste_image()
+                .and_then(|bytes| image::load_from_memory(&bytes).ok())
+                .unwrap_or_else(|| {
+                    eprintln!("No image found in the clipboard.");
+                    std::process::exit(1);
+                });
+            (img, info("clipboard"))
+        },
+        |file| {
+            let img = image::open(file).unwrap_or_else(|err| {
+                eprintln!("Failed to read {}: {err}", file.display());
+                std::process::exit(1);
+            });
+            (img, info("file"))
+        },
+    );
+
+    let format = args.format.unwrap_or_default();
+
+    let screenshot = Screenshot {
+        template: FilenameTemplate::from_options(
+            args.template.as_deref(),
+            args.no_save,
+            args.output,
+            &config.image_dir(),
+            format.extension(),
+        ),
+        edit: args.edit.as_deref().map(|edit| {
+            Editor::new(edit)
+                .unwrap_or_else(|err| error(clap::error::ErrorKind::InvalidValue, &err.to_string()))
+        }),
+        notify: !args.no_notify,
+        ocr: args.ocr.map(|lang| Ocr::from_args(lang, args.ocr_args)),
+        barcode: if args.qr {
+            Some(Barcode::Qr)
+        } else {
+            args.decode.then_some(Barcode::Any)
+        },
+        format,
+        quality: args.quality,
+        max_width: args.max_width,
+        max_height: args.max_height,
+        copy: copy_modes(&args.copy, CopyMode::Image),
+        primary: args.primary,
+        beautify: Beautify::from_args(&args.beautify_args),
+        upload: config.upload(args.upload.as_deref()),
+        // hooks are only run for new captures
+        hooks: Hooks::default(),
+        // only used when capturing
+        delay: None,
+        no_rounded_windows: false,
+        freeze: false,
+        icons: false,
+        slurp: None,
+        scale: None,
+        logical: false,
+        cursor: false,
+        stdout: false,
+    };
+
+    screenshot.process_image(img, info);
+}
@@ -0,0 +1,260 @@
+use std::{
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use execute::Execute;
+
+use crate::{
+    Rofi, check_programs,
+    cli::{last::LastAction, recent::RecentArgs},
+    config::Config,
+    history::{Entry, History},
+    hooks::CaptureKind,
+    last::Actions,
+};
+
+const THUMBNAIL_SIZE: u32 = 256;
+
+/// directory where thumbnails for the rofi menu are cached
+fn thumbnail_dir() -> PathBuf {
+    dirs::cache_dir()
+        .expect("could not get $XDG_CACHE_HOME")
+        .join("focal/thumbnails")
+}
+
+/// path of the cached thumbnail, which changes whenever the capture is modified, e.g. by editing
+fn thumbnail_path(dir: &Path, capture: &Path) -> PathBuf {
+    let mut hasher = std::hash::DefaultHasher::new();
+    capture.hash(&mut hasher);
+    std::fs::metadata(capture)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .hash(&mut hasher);
+
+    dir.join(format!("{:016x}.png", hasher.finish()))
+}
+
+/// creates a thumbnail for the capture if it isn't cached, returning None on failure
+/// or if ffmpeg is needed for a video but isn't installed
+fn thumbnail(dir: &Path, entry: &Entry) -> Option<PathBuf> {
+    let thumb = thumbnail_path(dir, &entry.path);
+    if thumb.exists() {
+        return Some(thumb);
+    }
+
+    std::fs::create_dir_all(dir).ok()?;
+
+    match entry.kind {
+        CaptureKind::Image => image::open(&entry.path)
+            .ok()?
+            .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+            .save(&thumb)
+            .ok()?,
+        CaptureKind::Video => {
+            // videos are shown without an icon if ffmpeg isn't installed
+            which::which("ffmpeg").ok()?;
+
+            Command::new("ffmpeg")
+                .arg("-i")
+                .arg(&entry.path)
+                // pick a representative frame instead of a possibly blank first frame
+                .arg("-vf")
+                .arg(format!(
+                    "thumbnail,scale={THUMBNAIL_SIZE}:{THUMBNAIL_SIZE}:force_original_aspect_ratio=decrease"
+                ))
+                .arg("-frames:v")
+                .arg("1")
+                .arg(&thumb)
+                .execute()
+                .ok()?;
+        }
+    }
+
+    thumb.exists().then_some(thumb)
+}
+
+/// text shown for the capture in the rofi menu
+fn label(entry: &Entry) -> String {
+    let time = chrono::DateTime::parse_from_rfc3339(&entry.time).map_or_else(
+        |_| entry.time.clone(),
+        |t| t.format("%b %d %H:%M").to_string(),
+    );
+    let name = entry
+        .path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    // each row is a single line, with no control characters that rofi treats as metadata
+    format!("{time}  {}  {name}", entry.area)
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect()
+}
+
+/// actions available for the capture, edit is only available for images
+fn actions(entry: &Entry, icons: bool) -> Vec<(String, LastAction)> {
+    let mut actions = vec![("󰆏\tCopy", LastAction::Copy), ("󰏌\tOpen", LastAction::Open)];
+    if entry.kind == CaptureKind::Image {
+        actions.push(("󰏫\tEdit", LastAction::Edit));
+    }
+    actions.push(("󰆴\tDelete", LastAction::Delete));
+    actions.push(("󰅍\tCopy path", LastAction::CopyPath));
+
+    actions
+        .into_iter()
+        .map(|(opt, action)| {
+            let opt = if icons {
+                opt
+            } else {
+                opt.split('\t').nth(1).expect("could not get nth(1)")
+            };
+            (opt.to_string(), action)
+        })
+        .collect()
+}
+
+pub fn main(mut args: RecentArgs) {
+    let config = Config::load(args.profile.as_deref());
+    config.apply_recent(&mut args);
+
+    check_programs(&["rofi"]);
+
+    let entries: Vec<_> = History::default()
+        .entries()
+        .expect("failed to read capture history")
+        .into_iter()
+        .rev()
+        .filter(|entry| args.kind.is_none_or(|kind| entry.is_kind(kind)))
+        .take(args.limit)
+        .collect();
+
+    if entries.is_empty() {
+        eprintln!("No captures found in history.");
+        std::process::exit(1);
+    }
+
+    let labels: Vec<_> = entries.iter().map(label).collect();
+    let mut rofi = Rofi::new(&labels).arg("-format").arg("i");
+    if !args.rofi_args.no_icons {
+        let dir = thumbnail_dir();
+        rofi = rofi.icons(entries.iter().map(|entry| thumbnail(&dir, entry)).collect());
+    }
+    if let Some(theme) = &args.rofi_args.theme {
+        rofi = rofi.theme(theme.clone());
+    }
+
+    // rofi returns the index of the selected row
+    let Some(entry) = rofi
+        .run()
+        .0
+        .parse::<usize>()
+        .ok()
+        .and_then(|idx| entries.get(idx))
+    else {
+        return;
+    };
+
+    let actions = actions(entry, !args.rofi_args.no_icons);
+    let opts: Vec<_> = actions.iter().map(|(opt, _)| opt).collect();
+    let mut rofi = Rofi::new(&opts)
+        .arg("-format")
+        .arg("i")
+        .message(&label(entry));
+    if let Some(theme) = &args.rofi_args.theme {
+        rofi = rofi.theme(theme.clone());
+    }
+
+    let Some((_, action)) = rofi
+        .run()
+        .0
+        .parse::<usize>()
+        .ok()
+        .and_then(|idx| actions.get(idx))
+    else {
+        return;
+    };
+
+    Actions {
+        ocr: config.ocr(None),
+        editor: config.image.editor,
+        notify: !args.no_notify,
+        primary: config.primary.unwrap_or_default(),
+    }
+    .perform(entry, *action);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::template::CaptureInfo;
+
+    #[test]
+    fn test_thumbnail() {
+        let dir = std::env::temp_dir().join(format!("focal-recent-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("failed to create test dir");
+
+        let path = dir.join("capture.png");
+        image::RgbImage::new(1024, 512)
+            .save(&path)
+            .expect("failed to write test image");
+
+        let info = CaptureInfo {
+            area: "selection".to_string(),
+            ..Default::default()
+        };
+        let entry = Entry::new(CaptureKind::Image, &path, &info, None);
+
+        let thumbs = dir.join("thumbnails");
+        let thumb = thumbnail(&thumbs, &entry).expect("failed to create thumbnail");
+        let dimensions = image::image_dimensions(&thumb).expect("failed to read thumbnail");
+        let cached = thumbnail(&thumbs, &entry);
+
+        let missing = Entry::new(CaptureKind::Image, &dir.join("missing.png"), &info, None);
+        let missing = thumbnail(&thumbs, &missing);
+        std::fs::remove_dir_all(&dir).expect("failed to remove test dir");
+
+        assert_eq!(dimensions, (THUMBNAIL_SIZE, THUMBNAIL_SIZE / 2));
+        assert_eq!(cached, Some(thumb), "thumbnails should be cached");
+        assert_eq!(missing, None);
+        assert!(label(&entry).ends_with("  selection  capture.png"));
+    }
+
+    #[test]
+    fn test_label() {
+        let info = CaptureInfo {
+            area: "window".to_string(),
+            ..Default::default()
+        };
+        let entries = [
+            Entry::new(
+                CaptureKind::Image,
+                Path::new("/tmp/GitHub | Firefox.png"),
+                &info,
+                None,
+            ),
+            Entry::new(
+                CaptureKind::Image,
+                Path::new("/tmp/line\nbreak.png"),
+                &info,
+                None,
+            ),
+            Entry::new(
+                CaptureKind::Video,
+                Path::new("/tmp/capture.mp4"),
+                &info,
+                None,
+            ),
+        ];
+
+        let labels: Vec<_> = entries.iter().map(label).collect();
+        assert!(labels[0].ends_with("  window  GitHub | Firefox.png"));
+        assert_eq!(
+            Rofi::new(&labels).input().lines().count(),
+            entries.len(),
+            "each capture should be a single row"
+        );
+    }
+}
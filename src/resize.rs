@@ -0,0 +1,142 @@
+use image::{
+    DynamicImage, RgbaImage,
+    imageops::{self, FilterType},
+};
+
+use crate::monitor::FocalMonitor;
+
+/// scales the image by the given factor
+pub fn scale(img: &DynamicImage, factor: f64) -> DynamicImage {
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_lossless
+    )]
+    let scaled = |dim: u32| ((dim as f64 * factor).round() as u32).max(1);
+
+    img.resize_exact(
+        scaled(img.width()),
+        scaled(img.height()),
+        FilterType::Lanczos3,
+    )
+}
+
+/// downscales the image to fit within the maximum dimensions, preserving the aspect ratio
+pub fn fit(img: DynamicImage, max_width: Option<u32>, max_height: Option<u32>) -> DynamicImage {
+    let max_width = max_width.unwrap_or(u32::MAX);
+    let max_height = max_height.unwrap_or(u32::MAX);
+
+    if img.width() <= max_width && img.height() <= max_height {
+        return img;
+    }
+
+    img.resize(max_width, max_height, FilterType::Lanczos3)
+}
+
+/// places the capture of each monitor according to the monitor layout
+/// captures are normalized to the given scale, so monitors with different scales line up
+pub fn composite(captures: &[(FocalMonitor, RgbaImage)], scale: f64) -> RgbaImage {
+    let origin_x = captures.iter().map(|(mon, _)| mon.x).min().unwrap_or(0);
+    let origin_y = captures.iter().map(|(mon, _)| mon.y).min().unwrap_or(0);
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let scaled = |n: f64| (n * scale).round().max(0.0) as u32;
+
+    // the logical size is derived from the capture, which is already rotated
+    let placed: Vec<_> = captures
+        .iter()
+        .map(|(mon, img)| {
+            let factor = f64::from(mon.scale);
+            let w = scaled(f64::from(img.width()) / factor).max(1);
+            let h = scaled(f64::from(img.height()) / factor).max(1);
+            let img = if img.dimensions() == (w, h) {
+                img.clone()
+            } else {
+                imageops::resize(img, w, h, FilterType::Lanczos3)
+            };
+
+            (
+                scaled(f64::from(mon.x - origin_x)),
+                scaled(f64::from(mon.y - origin_y)),
+                img,
+            )
+        })
+        .collect();
+
+    let width = placed.iter().map(|(x, _, img)| x + img.width()).max();
+    let height = placed.iter().map(|(_, y, img)| y + img.height()).max();
+    let mut canvas = RgbaImage::new(width.unwrap_or(1), height.unwrap_or(1));
+    for (x, y, img) in placed {
+        imageops::replace(&mut canvas, &img, i64::from(x), i64::from(y));
+    }
+
+    canvas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit() {
+        let img = DynamicImage::new_rgba8(400, 200);
+
+        let fitted = fit(img.clone(), Some(100), None);
+        assert_eq!((fitted.width(), fitted.height()), (100, 50));
+
+        let fitted = fit(img.clone(), Some(300), Some(50));
+        assert_eq!((fitted.width(), fitted.height()), (100, 50));
+
+        let fitted = fit(img, Some(800), Some(800));
+        assert_eq!(
+            (fitted.width(), fitted.height()),
+            (400, 200),
+            "images should never be upscaled"
+        );
+    }
+
+    #[test]
+    fn test_scale() {
+        let scaled = scale(&DynamicImage::new_rgba8(400, 200), 0.5);
+        assert_eq!((scaled.width(), scaled.height()), (200, 100));
+    }
+
+    #[test]
+    fn test_composite() {
+        use crate::monitor::Rotation;
+        use image::Rgba;
+
+        let monitor = |x, y, scale| FocalMonitor {
+            name: String::new(),
+            x,
+            y,
+            w: 0,
+            h: 0,
+            scale,
+            rotation: Rotation::Normal,
+        };
+        let red = Rgba([255, 0, 0, 255]);
+        let blue = Rgba([0, 0, 255, 255]);
+
+        // a 200x100 monitor at scale 2 to the left of a 100x200 portrait monitor at scale 1
+        let captures = [
+            (monitor(-200, 0, 2.0), RgbaImage::from_pixel(400, 200, red)),
+            (monitor(0, 50, 1.0), RgbaImage::from_pixel(100, 200, blue)),
+        ];
+
+        let img = composite(&captures, 1.0);
+        assert_eq!(img.dimensions(), (300, 250));
+        assert_eq!(*img.get_pixel(199, 99), red);
+        assert_eq!(*img.get_pixel(200, 50), blue);
+        assert_eq!(
+            *img.get_pixel(0, 249),
+            Rgba([0, 0, 0, 0]),
+            "areas without monitors should be transparent"
+        );
+
+        let img = composite(&captures, 2.0);
+        assert_eq!(img.dimensions(), (600, 500));
+        assert_eq!(*img.get_pixel(399, 199), red);
+        assert_eq!(*img.get_pixel(400, 100), blue);
+    }
+}
@@ -8,6 +8,7 @@ use execute::Execute;
 
 pub struct Rofi {
     choices: Vec<String>,
+    icons: Vec<Option<PathBuf>>,
     command: Command,
     message: String,
     theme: PathBuf,
@@ -24,9 +25,6 @@ impl Rofi {
             // hide the search input
             .arg("-theme-str")
             .arg("mainbox { children: [listview, message]; }")
-            // use | as separator
-            .arg("-sep")
-            .arg("|")
             .arg("-disable-history")
             .arg("true")
             .arg("-cycle")
@@ -34,6 +32,7 @@ impl Rofi {
 
         Self {
             choices: choices.iter().map(|s| s.as_ref().to_string()).collect(),
+            icons: Vec::new(),
             command: cmd,
             message: String::new(),
             theme: dirs::cache_dir()
@@ -54,13 +53,36 @@ impl Rofi {
         self
     }
 
+    /// icons for each choice, choices without an icon are shown as text only
+    #[must_use]
+    pub fn icons(mut self, icons: Vec<Option<PathBuf>>) -> Self {
+        self.icons = icons;
+        self.command.arg("-show-icons");
+        self
+    }
+
     #[must_use]
     pub fn message(mut self, message: &str) -> Self {
         self.message = message.to_string();
         self
     }
 
+    /// rows passed to rofi, one per line
+    pub fn input(&self) -> String {
+        // icons are set using the row metadata, e.g. choice\0icon\x1f/path/to/icon
+        self.choices
+            .iter()
+            .enumerate()
+            .map(|(idx, choice)| match self.icons.get(idx) {
+                Some(Some(icon)) => format!("{choice}\0icon\x1f{}", icon.display()),
+                _ => choice.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
     pub fn run(self) -> (String, i32) {
+        let input = self.input();
         let mut cmd = self.command;
 
         if self.theme.exists() {
@@ -83,8 +105,7 @@ impl Rofi {
 
         let output = cmd
             .stdout(Stdio::piped())
-            // use | as separator
-            .execute_input_output(self.choices.join("|").as_bytes())
+            .execute_input_output(input.as_bytes())
             .expect("failed to run rofi");
 
         let exit_code = output.status.code().expect("rofi has not exited");
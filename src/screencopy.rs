@@ -0,0 +1,620 @@
+use std::{
+    fmt,
+    fs::File,
+    os::{fd::AsFd, unix::fs::FileExt},
+};
+
+use image::{DynamicImage, Rgba, RgbaImage, imageops};
+use wayland_client::{
+    Connection, Dispatch, EventQueue, Proxy, QueueHandle, WEnum, delegate_noop,
+    globals::{GlobalListContents, registry_queue_init},
+    protocol::{
+        wl_buffer::WlBuffer,
+        wl_output::{self, Transform, WlOutput},
+        wl_registry::WlRegistry,
+        wl_shm::{Format, WlShm},
+        wl_shm_pool::WlShmPool,
+    },
+};
+use wayland_protocols::xdg::xdg_output::zv1::client::{
+    zxdg_output_manager_v1::ZxdgOutputManagerV1,
+    zxdg_output_v1::{self, ZxdgOutputV1},
+};
+use wayland_protocols_wlr::screencopy::v1::client::{
+    zwlr_screencopy_frame_v1::{self, Flags, ZwlrScreencopyFrameV1},
+    zwlr_screencopy_manager_v1::ZwlrScreencopyManagerV1,
+};
+
+use crate::SlurpGeom;
+
+#[derive(Debug)]
+pub struct ScreencopyError {
+    message: String,
+    unsupported: bool,
+}
+
+impl ScreencopyError {
+    fn new(msg: impl fmt::Display) -> Self {
+        Self {
+            message: msg.to_string(),
+            unsupported: false,
+        }
+    }
+
+    /// the compositor does not advertise a protocol needed for capturing
+    fn unsupported(protocol: &str) -> Self {
+        Self {
+            message: format!("compositor does not support {protocol}"),
+            unsupported: true,
+        }
+    }
+
+    pub const fn is_unsupported(&self) -> bool {
+        self.unsupported
+    }
+}
+
+impl fmt::Display for ScreencopyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// name and logical geometry of an output, as reported by xdg-output
+#[derive(Debug, Clone)]
+struct OutputInfo {
+    name: String,
+    geom: SlurpGeom,
+    transform: Transform,
+}
+
+impl Default for OutputInfo {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            geom: SlurpGeom::default(),
+            transform: Transform::Normal,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct BufferInfo {
+    format: Format,
+    width: u32,
+    height: u32,
+    stride: u32,
+}
+
+/// events received for the frame being captured
+#[derive(Debug, Default)]
+struct Frame {
+    buffers: Vec<BufferInfo>,
+    buffer_done: bool,
+    y_invert: bool,
+    /// true if the frame was copied, false if it failed
+    copied: Option<bool>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    outputs: Vec<OutputInfo>,
+    frame: Frame,
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for State {
+    fn event(
+        _: &mut Self,
+        _: &WlRegistry,
+        _: <WlRegistry as Proxy>::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        // outputs added after connecting are not captured
+    }
+}
+
+impl Dispatch<WlOutput, usize> for State {
+    fn event(
+        state: &mut Self,
+        _: &WlOutput,
+        event: wl_output::Event,
+        idx: &usize,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let output = &mut state.outputs[*idx];
+        match event {
+            wl_output::Event::Geometry {
+                transform: WEnum::Value(transform),
+                ..
+            } => output.transform = transform,
+            // xdg-output names are preferred, but are only sent from version 2
+            wl_output::Event::Name { name } if output.name.is_empty() => output.name = name,
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<ZxdgOutputV1, usize> for State {
+    fn event(
+        state: &mut Self,
+        _: &ZxdgOutputV1,
+        event: zxdg_output_v1::Event,
+        idx: &usize,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let output = &mut state.outputs[*idx];
+        match event {
+            zxdg_output_v1::Event::LogicalPosition { x, y } => {
+                output.geom.x = x;
+                output.geom.y = y;
+            }
+            zxdg_output_v1::Event::LogicalSize { width, height } => {
+                output.geom.w = width;
+                output.geom.h = height;
+            }
+            zxdg_output_v1::Event::Name { name } => output.name = name,
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<ZwlrScreencopyFrameV1, ()> for State {
+    fn event(
+        state: &mut Self,
+        _: &ZwlrScreencopyFrameV1,
+        event: zwlr_screencopy_frame_v1::Event,
+        (): &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let frame = &mut state.frame;
+        match event {
+            zwlr_screencopy_frame_v1::Event::Buffer {
+                format: WEnum::Value(format),
+                width,
+                height,
+                stride,
+            } => frame.buffers.push(BufferInfo {
+                format,
+                width,
+                height,
+                stride,
+            }),
+            zwlr_screencopy_frame_v1::Event::Flags {
+                flags: WEnum::Value(flags),
+            } => frame.y_invert = flags.contains(Flags::YInvert),
+            zwlr_screencopy_frame_v1::Event::BufferDone => frame.buffer_done = true,
+            zwlr_screencopy_frame_v1::Event::Ready { .. } => frame.copied = Some(true),
+            zwlr_screencopy_frame_v1::Event::Failed => frame.copied = Some(false),
+            _ => {}
+        }
+    }
+}
+
+delegate_noop!(State: ignore WlShm);
+delegate_noop!(State: ignore WlBuffer);
+delegate_noop!(State: WlShmPool);
+delegate_noop!(State: ZxdgOutputManagerV1);
+delegate_noop!(State: ZwlrScreencopyManagerV1);
+
+/// 10 bit channels are truncated to 8 bits
+#[allow(clippy::cast_possible_truncation)]
+const fn channel10(v: u32, shift: u32) -> u8 {
+    (((v >> shift) & 0x3ff) >> 2) as u8
+}
+
+/// returns the function converting a pixel of the shm format to rgba, if supported
+fn converter(format: Format) -> Option<fn([u8; 4]) -> [u8; 4]> {
+    // formats are little endian, e.g. argb8888 is stored as b, g, r, a
+    Some(match format {
+        Format::Argb8888 => |[b, g, r, a]| [r, g, b, a],
+        Format::Xrgb8888 => |[b, g, r, _]| [r, g, b, 255],
+        Format::Abgr8888 => |px| px,
+        Format::Xbgr8888 => |[r, g, b, _]| [r, g, b, 255],
+        Format::Xrgb2101010 | Format::Argb2101010 => |px| {
+            let v = u32::from_le_bytes(px);
+            [channel10(v, 20), channel10(v, 10), channel10(v, 0), 255]
+        },
+        Format::Xbgr2101010 | Format::Abgr2101010 => |px| {
+            let v = u32::from_le_bytes(px);
+            [channel10(v, 0), channel10(v, 10), channel10(v, 20), 255]
+        },
+        _ => return None,
+    })
+}
+
+/// converts a shm buffer to rgba, returning None for unsupported formats
+fn to_rgba(buffer: &BufferInfo, data: &[u8]) -> Option<RgbaImage> {
+    let convert = converter(buffer.format)?;
+
+    let BufferInfo {
+        width,
+        height,
+        stride,
+        ..
+    } = *buffer;
+    if stride < width * 4 || data.len() < (stride * height) as usize {
+        return None;
+    }
+
+    Some(RgbaImage::from_fn(width, height, |x, y| {
+        let idx = (y * stride + x * 4) as usize;
+        Rgba(convert([
+            data[idx],
+            data[idx + 1],
+            data[idx + 2],
+            data[idx + 3],
+        ]))
+    }))
+}
+
+/// orients the frame the way it is shown on the output
+fn orient(img: RgbaImage, y_invert: bool, transform: Transform) -> RgbaImage {
+    let img = if y_invert {
+        imageops::flip_vertical(&img)
+    } else {
+        img
+    };
+
+    // frames are in the orientation of the panel, rotate clockwise by the output transform
+    let img = match transform {
+        Transform::_90 | Transform::Flipped90 => imageops::rotate90(&img),
+        Transform::_180 | Transform::Flipped180 => imageops::rotate180(&img),
+        Transform::_270 | Transform::Flipped270 => imageops::rotate270(&img),
+        _ => img,
+    };
+
+    match transform {
+        Transform::Flipped
+        | Transform::Flipped90
+        | Transform::Flipped180
+        | Transform::Flipped270 => imageops::flip_horizontal(&img),
+        _ => img,
+    }
+}
+
+/// connection to the compositor with the globals needed for capturing
+struct Session {
+    queue: EventQueue<State>,
+    state: State,
+    shm: WlShm,
+    manager: ZwlrScreencopyManagerV1,
+    outputs: Vec<WlOutput>,
+}
+
+impl Session {
+    fn connect() -> Result<Self, ScreencopyError> {
+        let conn = Connection::connect_to_env().map_err(ScreencopyError::new)?;
+        let (globals, mut queue) =
+            registry_queue_init::<State>(&conn).map_err(ScreencopyError::new)?;
+        let qh = queue.handle();
+
+        let manager = globals
+            .bind::<ZwlrScreencopyManagerV1, _, _>(&qh, 1..=3, ())
+            .map_err(|_| ScreencopyError::unsupported("wlr-screencopy"))?;
+        let shm = globals
+            .bind::<WlShm, _, _>(&qh, 1..=1, ())
+            .map_err(ScreencopyError::new)?;
+        let xdg_output_manager = globals
+            .bind::<ZxdgOutputManagerV1, _, _>(&qh, 2..=3, ())
+            .map_err(|_| ScreencopyError::unsupported("xdg-output"))?;
+
+        let outputs: Vec<_> = globals
+            .contents()
+            .clone_list()
+            .into_iter()
+            .filter(|global| global.interface == WlOutput::interface().name)
+            .enumerate()
+            .map(|(idx, global)| {
+                globals.registry().bind::<WlOutput, _, _>(
+                    global.name,
+                    global.version.min(4),
+                    &qh,
+                    idx,
+                )
+            })
+            .collect();
+
+        for (idx, output) in outputs.iter().enumerate() {
+            xdg_output_manager.get_xdg_output(output, &qh, idx);
+        }
+
+        let mut state = State {
+            outputs: vec![OutputInfo::default(); outputs.len()],
+            ..Default::default()
+        };
+        queue.roundtrip(&mut state).map_err(ScreencopyError::new)?;
+
+        Ok(Self {
+            queue,
+            state,
+            shm,
+            manager,
+            outputs,
+        })
+    }
+
+    fn dispatch_until(&mut self, done: impl Fn(&Frame) -> bool) -> Result<(), ScreencopyError> {
+        while !done(&self.state.frame) {
+            self.queue
+                .blocking_dispatch(&mut self.state)
+                .map_err(ScreencopyError::new)?;
+        }
+        Ok(())
+    }
+
+    /// captures a region of the output in its logical coordinates
+    fn capture(
+        &mut self,
+        idx: usize,
+        region: &SlurpGeom,
+        cursor: bool,
+    ) -> Result<RgbaImage, ScreencopyError> {
+        let qh = self.queue.handle();
+        self.state.frame = Frame::default();
+
+        let frame = self.manager.capture_output_region(
+            i32::from(cursor),
+            &self.outputs[idx],
+            region.x,
+            region.y,
+            region.w,
+            region.h,
+            &qh,
+            (),
+        );
+
+        // buffer_done is only sent from version 3, older versions only send shm buffers
+        let version = self.manager.version();
+        self.dispatch_until(|frame| {
+            frame.copied.is_some()
+                || frame.buffer_done
+                || (version < 3 && !frame.buffers.is_empty())
+        })?;
+
+        let buffer = self
+            .state
+            .frame
+            .buffers
+            .iter()
+            .find(|buffer| converter(buffer.format).is_some())
+            .copied()
+            .ok_or_else(|| ScreencopyError::new("no supported buffer format was offered"))?;
+
+        let size = buffer.stride * buffer.height;
+        let file = File::from(
+            rustix::fs::memfd_create("focal-screencopy", rustix::fs::MemfdFlags::CLOEXEC)
+                .map_err(ScreencopyError::new)?,
+        );
+        file.set_len(u64::from(size))
+            .map_err(ScreencopyError::new)?;
+
+        let to_i32 = |n: u32| i32::try_from(n).map_err(ScreencopyError::new);
+        let pool = self.shm.create_pool(file.as_fd(), to_i32(size)?, &qh, ());
+        let wl_buffer = pool.create_buffer(
+            0,
+            to_i32(buffer.width)?,
+            to_i32(buffer.height)?,
+            to_i32(buffer.stride)?,
+            buffer.format,
+            &qh,
+            (),
+        );
+
+        frame.copy(&wl_buffer);
+        self.dispatch_until(|frame| frame.copied.is_some())?;
+
+        frame.destroy();
+        wl_buffer.destroy();
+        pool.destroy();
+
+        if self.state.frame.copied != Some(true) {
+            return Err(ScreencopyError::new("compositor failed to copy the frame"));
+        }
+
+        // the buffer is read back from the memfd, so no mapping is needed
+        let mut data = vec![0; size as usize];
+        file.read_exact_at(&mut data, 0)
+            .map_err(ScreencopyError::new)?;
+
+        let img = to_rgba(&buffer, &data)
+            .ok_or_else(|| ScreencopyError::new("invalid buffer received"))?;
+        Ok(orient(
+            img,
+            self.state.frame.y_invert,
+            self.state.outputs[idx].transform,
+        ))
+    }
+}
+
+/// captures the screen in process using the wlr-screencopy protocol
+#[derive(Default)]
+pub struct Screencopy {
+    monitor: String,
+    geometry: Option<SlurpGeom>,
+    scale: Option<f64>,
+    cursor: bool,
+}
+
+impl Screencopy {
+    #[must_use]
+    pub fn geometry(mut self, geometry: &str) -> Self {
+        self.geometry = geometry.parse().ok();
+        self
+    }
+
+    #[must_use]
+    pub fn monitor(mut self, monitor: &str) -> Self {
+        self.monitor = monitor.to_string();
+        self
+    }
+
+    /// scale of the captured image, defaults to the highest scale of the captured outputs
+    #[must_use]
+    pub const fn scale(mut self, scale: Option<f64>) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    #[must_use]
+    pub const fn cursor(mut self, cursor: bool) -> Self {
+        self.cursor = cursor;
+        self
+    }
+
+    /// captures the monitor, geometry or all outputs if neither is set
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    pub fn capture(&self) -> Result<DynamicImage, ScreencopyError> {
+        let mut session = Session::connect()?;
+        let outputs = session.state.outputs.clone();
+
+        let region = if !self.monitor.is_empty() {
+            outputs
+                .iter()
+                .find(|output| output.name == self.monitor)
+                .map(|output| output.geom)
+                .ok_or_else(|| ScreencopyError::new(format!("no output named {}", self.monitor)))?
+        } else if let Some(geometry) = self.geometry {
+            geometry
+        } else {
+            outputs
+                .iter()
+                .map(|output| output.geom)
+                .reduce(|a, b| a.union(&b))
+                .ok_or_else(|| ScreencopyError::new("no outputs found"))?
+        };
+
+        let mut parts = Vec::new();
+        for (idx, output) in outputs.iter().enumerate() {
+            if let Some(part) = region.intersection(&output.geom) {
+                let local = SlurpGeom {
+                    x: part.x - output.geom.x,
+                    y: part.y - output.geom.y,
+                    ..part
+                };
+                parts.push((part, session.capture(idx, &local, self.cursor)?));
+            }
+        }
+
+        if parts.is_empty() {
+            return Err(ScreencopyError::new(format!(
+                "{region} is not on any output"
+            )));
+        }
+
+        let scale = self.scale.unwrap_or_else(|| {
+            parts
+                .iter()
+                .map(|(part, img)| f64::from(img.width()) / f64::from(part.w))
+                .fold(1.0, f64::max)
+        });
+        let scaled = |n: i32| (f64::from(n) * scale).round() as i64;
+
+        // outputs with a different scale are resized to the scale of the capture
+        let mut canvas = RgbaImage::new(scaled(region.w) as u32, scaled(region.h) as u32);
+        for (part, img) in parts {
+            let (w, h) = (scaled(part.w) as u32, scaled(part.h) as u32);
+            let img = if img.dimensions() == (w, h) {
+                img
+            } else {
+                imageops::resize(&img, w, h, imageops::FilterType::Triangle)
+            };
+
+            imageops::replace(
+                &mut canvas,
+                &img,
+                scaled(part.x - region.x),
+                scaled(part.y - region.y),
+            );
+        }
+
+        Ok(DynamicImage::ImageRgba8(canvas))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffer(format: Format, width: u32, height: u32, stride: u32) -> BufferInfo {
+        BufferInfo {
+            format,
+            width,
+            height,
+            stride,
+        }
+    }
+
+    #[test]
+    fn test_to_rgba() {
+        // 1x2 image with padding at the end of each row
+        let data = [10, 20, 30, 40, 0, 0, 0, 0, 50, 60, 70, 80, 0, 0, 0, 0];
+
+        let img = to_rgba(&buffer(Format::Xrgb8888, 1, 2, 8), &data).expect("supported format");
+        assert_eq!(img.get_pixel(0, 0), &Rgba([30, 20, 10, 255]));
+        assert_eq!(img.get_pixel(0, 1), &Rgba([70, 60, 50, 255]));
+
+        let img = to_rgba(&buffer(Format::Abgr8888, 1, 2, 8), &data).expect("supported format");
+        assert_eq!(img.get_pixel(0, 1), &Rgba([50, 60, 70, 80]));
+
+        let red = (0x3ff_u32 << 20).to_le_bytes();
+        let img = to_rgba(&buffer(Format::Xrgb2101010, 1, 1, 4), &red).expect("supported format");
+        assert_eq!(img.get_pixel(0, 0), &Rgba([255, 0, 0, 255]));
+
+        assert!(
+            to_rgba(&buffer(Format::Rgb565, 1, 1, 4), &data).is_none(),
+            "unsupported formats should not be converted"
+        );
+        assert!(
+            to_rgba(&buffer(Format::Xrgb8888, 1, 4, 8), &data).is_none(),
+            "short buffers should not be converted"
+        );
+    }
+
+    #[test]
+    fn test_orient() {
+        // 2x1 image of a red and a blue pixel
+        let red = Rgba([255, 0, 0, 255]);
+        let blue = Rgba([0, 0, 255, 255]);
+        let img = RgbaImage::from_fn(2, 1, |x, _| if x == 0 { red } else { blue });
+
+        let rotated = orient(img.clone(), false, Transform::_90);
+        assert_eq!(rotated.dimensions(), (1, 2));
+        assert_eq!(rotated.get_pixel(0, 0), &red, "should rotate clockwise");
+
+        let rotated = orient(img.clone(), false, Transform::_270);
+        assert_eq!(rotated.get_pixel(0, 0), &blue);
+
+        let flipped = orient(img.clone(), false, Transform::Flipped);
+        assert_eq!(flipped.get_pixel(0, 0), &blue);
+
+        let inverted = orient(img, true, Transform::_90);
+        assert_eq!(inverted.get_pixel(0, 0), &red);
+    }
+
+    /// run against a headless compositor, e.g.
+    /// `WLR_BACKENDS=headless WLR_RENDERER=pixman sway`
+    #[test]
+    #[ignore = "requires a compositor supporting wlr-screencopy"]
+    fn test_capture() {
+        let img = Screencopy::default()
+            .geometry("0,0 20x10")
+            .scale(Some(1.0))
+            .capture()
+            .expect("failed to capture");
+        assert_eq!((img.width(), img.height()), (20, 10));
+
+        let img = Screencopy::default()
+            .capture()
+            .expect("failed to capture all outputs");
+        assert!(img.width() > 0 && img.height() > 0);
+    }
+}
@@ -0,0 +1,253 @@
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::Range,
+    process::Command,
+    time::Duration,
+};
+
+use execute::Execute;
+use image::{RgbaImage, imageops};
+
+use crate::cli::image::ScrollInput;
+
+/// time between captures
+const INTERVAL: Duration = Duration::from_millis(250);
+/// capturing stops once the selection has not changed for this long while scrolling manually
+const IDLE_TIMEOUT: Duration = Duration::from_secs(3);
+/// capturing stops once scrolling automatically no longer changes the selection for this long
+const AUTO_IDLE_TIMEOUT: Duration = Duration::from_secs(1);
+/// stops capturing content that never stops changing, e.g. videos
+const MAX_FRAMES: usize = 200;
+/// minimum number of rows that have to overlap between frames
+const MIN_OVERLAP: usize = 8;
+
+/// scrolls down the window under the cursor, or the focused window for wtype
+fn scroll_down(input: ScrollInput) {
+    let mut cmd = Command::new(input.program());
+    match input {
+        ScrollInput::Ydotool => cmd.args(["mousemove", "--wheel", "-x", "0", "-y", "-1"]),
+        ScrollInput::Wtype => cmd.args(["-k", "Down", "-k", "Down", "-k", "Down"]),
+    };
+
+    cmd.execute()
+        .unwrap_or_else(|_| panic!("failed to scroll using {}", input.program()));
+}
+
+/// captures frames until the selection stops changing, scrolling automatically if an input is given
+pub fn capture_frames(
+    mut capture: impl FnMut() -> RgbaImage,
+    input: Option<ScrollInput>,
+) -> Vec<RgbaImage> {
+    let timeout = if input.is_some() {
+        AUTO_IDLE_TIMEOUT
+    } else {
+        IDLE_TIMEOUT
+    };
+
+    let mut frames = vec![capture()];
+    let mut idle = Duration::ZERO;
+    while idle < timeout && frames.len() < MAX_FRAMES {
+        if let Some(input) = input {
+            scroll_down(input);
+        }
+        std::thread::sleep(INTERVAL);
+
+        let frame = capture();
+        if frames.last() == Some(&frame) {
+            idle += INTERVAL;
+        } else {
+            idle = Duration::ZERO;
+            frames.push(frame);
+        }
+    }
+
+    frames
+}
+
+fn row_hashes(img: &RgbaImage) -> Vec<u64> {
+    img.as_raw()
+        .chunks_exact(img.width() as usize * 4)
+        .map(|row| {
+            let mut hasher = DefaultHasher::new();
+            row.hash(&mut hasher);
+            hasher.finish()
+        })
+        .collect()
+}
+
+/// number of rows at the start of both frames that are the same
+fn common_prefix<'a>(a: impl Iterator<Item = &'a u64>, b: impl Iterator<Item = &'a u64>) -> usize {
+    a.zip(b).take_while(|(a, b)| a == b).count()
+}
+
+/// returns how many rows the content moved up between frames, 0 if it did not move
+/// returns None if the frames do not overlap
+fn find_offset(prev: &[u64], next: &[u64]) -> Option<usize> {
+    if prev == next {
+        return Some(0);
+    }
+
+    let height = prev.len().min(next.len());
+    let mut best = None;
+    let mut best_score = 0;
+
+    for offset in 1..=height.saturating_sub(MIN_OVERLAP) {
+        let overlap = height - offset;
+        let rows = prev[offset..height].iter().zip(&next[..overlap]);
+
+        // allow a few differing rows, e.g. from a blinking cursor
+        let mismatches = rows.clone().filter(|(a, b)| a != b).count();
+        if mismatches * 20 > overlap {
+            continue;
+        }
+
+        // rows repeating the previous row, e.g. blank space, match at any offset
+        let score = rows
+            .enumerate()
+            .filter(|&(idx, (a, b))| a == b && prev[offset + idx - 1] != *a)
+            .count();
+
+        if score > best_score {
+            best = Some(offset);
+            best_score = score;
+        }
+    }
+
+    best
+}
+
+/// stitches the frames of a scrolling capture into one tall image
+/// frames that did not scroll or do not overlap the previous frame are skipped
+pub fn stitch(frames: &[RgbaImage]) -> Option<RgbaImage> {
+    let first = frames.first()?;
+    let (width, height) = first.dimensions();
+    let frames: Vec<_> = frames
+        .iter()
+        .filter(|frame| frame.dimensions() == (width, height))
+        .collect();
+    let hashes: Vec<_> = frames.iter().map(|frame| row_hashes(frame)).collect();
+    let h = height as usize;
+
+    // fixed headers and footers stay in place while the content between them scrolls
+    let fixed = |count: &dyn Fn(&[u64], &[u64]) -> usize| {
+        hashes
+            .windows(2)
+            .map(|pair| count(&pair[0], &pair[1]))
+            .filter(|&rows| rows < h)
+            .min()
+            .unwrap_or(0)
+    };
+    let mut header = fixed(&|a, b| common_prefix(a.iter(), b.iter()));
+    let mut footer = fixed(&|a, b| common_prefix(a.iter().rev(), b.iter().rev()));
+    if header + footer + MIN_OVERLAP > h {
+        header = 0;
+        footer = 0;
+    }
+    let content = header..h - footer;
+
+    // rows of each frame to be added to the stitched image
+    let mut strips: Vec<(usize, Range<usize>)> = vec![(0, 0..content.end)];
+    let mut prev = 0;
+    for (idx, frame_hashes) in hashes.iter().enumerate().skip(1) {
+        match find_offset(
+            &hashes[prev][content.clone()],
+            &frame_hashes[content.clone()],
+        ) {
+            Some(0) | None => {}
+            Some(offset) => {
+                strips.push((idx, content.end - offset..content.end));
+                prev = idx;
+            }
+        }
+    }
+    strips.push((prev, content.end..h));
+
+    let total = strips.iter().map(|(_, rows)| rows.len()).sum::<usize>();
+    let mut stitched = RgbaImage::new(width, u32::try_from(total).ok()?);
+    let mut y = 0;
+    for (idx, rows) in strips {
+        let (start, len) = (
+            u32::try_from(rows.start).ok()?,
+            u32::try_from(rows.len()).ok()?,
+        );
+        let strip = imageops::crop_imm(frames[idx], 0, start, width, len).to_image();
+        imageops::replace(&mut stitched, &strip, 0, y);
+        y += i64::from(len);
+    }
+
+    Some(stitched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    const WIDTH: u32 = 4;
+    const HEIGHT: u32 = 100;
+
+    /// a page with distinct rows, and a blank area in the middle
+    fn page(height: u32) -> RgbaImage {
+        RgbaImage::from_fn(WIDTH, height, |x, y| {
+            if (200..240).contains(&y) {
+                Rgba([255, 255, 255, 255])
+            } else {
+                let [a, b, ..] = y.to_le_bytes();
+                Rgba([a, b, u8::try_from(x).unwrap_or_default(), 255])
+            }
+        })
+    }
+
+    /// the page scrolled down by offset, with a fixed header and footer
+    fn frame(page: &RgbaImage, offset: u32, header: u32, footer: u32) -> RgbaImage {
+        let mut frame = RgbaImage::from_pixel(WIDTH, HEIGHT, Rgba([255, 0, 0, 255]));
+        let content = imageops::crop_imm(page, 0, offset, WIDTH, HEIGHT - header - footer);
+        imageops::replace(&mut frame, &*content, 0, i64::from(header));
+        frame
+    }
+
+    #[test]
+    fn test_stitch() {
+        let page = page(400);
+        let frames: Vec<_> = [0, 30, 30, 95, 180, 230, 300]
+            .iter()
+            .map(|&offset| frame(&page, offset, 0, 0))
+            .collect();
+
+        let stitched = stitch(&frames).expect("frames should be stitched");
+        assert_eq!(stitched, page, "repeated frames should be skipped");
+    }
+
+    #[test]
+    fn test_stitch_fixed() {
+        let page = page(300);
+        let frames: Vec<_> = [0, 40, 100, 170]
+            .iter()
+            .map(|&offset| frame(&page, offset, 10, 5))
+            .collect();
+
+        let stitched = stitch(&frames).expect("frames should be stitched");
+        assert_eq!(stitched.dimensions(), (WIDTH, 10 + 170 + 85 + 5));
+        assert_eq!(
+            imageops::crop_imm(&stitched, 0, 10, WIDTH, 255).to_image(),
+            imageops::crop_imm(&page, 0, 0, WIDTH, 255).to_image(),
+            "the header and footer should only be included once"
+        );
+    }
+
+    #[test]
+    fn test_stitch_no_overlap() {
+        let page = page(400);
+        let frames: Vec<_> = [0, 50, 250]
+            .iter()
+            .map(|&offset| frame(&page, offset, 0, 0))
+            .collect();
+
+        let stitched = stitch(&frames).expect("frames should be stitched");
+        assert_eq!(
+            stitched,
+            imageops::crop_imm(&page, 0, 0, WIDTH, 150).to_image(),
+            "frames without any overlap should be skipped"
+        );
+    }
+}
@@ -4,7 +4,10 @@ use std::{
     process::{Command, Stdio},
 };
 
-use crate::{focal_monitor, is_hyprland};
+use crate::{
+    focal_monitor, is_hyprland,
+    monitor::{FocalMonitor, FocalWindow},
+};
 
 #[derive(Debug)]
 pub struct ParseError {
@@ -69,16 +72,43 @@ const fn round2(n: i32) -> i32 {
 }
 
 impl SlurpGeom {
+    /// returns the monitor containing the top left corner of the geometry
+    pub fn monitor(&self) -> Option<FocalMonitor> {
+        let Self { x, y, .. } = *self;
+
+        focal_monitor()
+            .all()
+            .into_iter()
+            .find(|m| x >= m.x && x <= m.x + m.w && y >= m.y && y <= m.y + m.h)
+    }
+
+    /// returns the overlapping area of both geometries, if any
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let w = (self.x + self.w).min(other.x + other.w) - x;
+        let h = (self.y + self.h).min(other.y + other.h) - y;
+
+        (w > 0 && h > 0).then_some(Self { w, h, x, y })
+    }
+
+    /// returns the bounding box of both geometries
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let w = (self.x + self.w).max(other.x + other.w) - x;
+        let h = (self.y + self.h).max(other.y + other.h) - y;
+
+        Self { w, h, x, y }
+    }
+
     pub fn to_ffmpeg_geom(self) -> (String, String) {
         let Self { x, y, w, h } = self;
 
-        let monitors = focal_monitor().all();
-        let mon = monitors
-            .iter()
-            .find(|m| x >= m.x && x <= m.x + m.w && y >= m.y && y <= m.y + m.h)
-            .unwrap_or_else(|| {
-                panic!("No monitor found for slurp region");
-            });
+        let mon = self.monitor().unwrap_or_else(|| {
+            panic!("No monitor found for slurp region");
+        });
 
         // get coordinates relative to monitor
         let (mut w, mut h) = (w, h);
@@ -109,7 +139,7 @@ impl SlurpGeom {
             }
         );
 
-        (mon.name.clone(), filter)
+        (mon.name, filter)
     }
 
     pub fn hyprland_disable_fade_animation() -> Option<String> {
@@ -146,9 +176,9 @@ impl SlurpGeom {
         }
     }
 
-    /// returns the selected geometry and if a window was selected
-    pub fn prompt(slurp_args: Option<&str>) -> (Self, bool) {
-        let window_geoms = focal_monitor().window_geoms();
+    /// returns the selected geometry and the window if one was selected, or None if cancelled
+    pub fn prompt(slurp_args: Option<&str>) -> Option<(Self, Option<FocalWindow>)> {
+        let windows = focal_monitor().windows();
 
         let orig_fade_anim = if is_hyprland() {
             Self::hyprland_disable_fade_animation()
@@ -156,9 +186,9 @@ impl SlurpGeom {
             None
         };
 
-        let slurp_geoms = window_geoms
+        let slurp_geoms = windows
             .iter()
-            .map(std::string::ToString::to_string)
+            .map(|(geom, _)| geom.to_string())
             .collect::<Vec<_>>()
             .join("\n");
 
@@ -194,19 +224,62 @@ impl SlurpGeom {
         match sel {
             Ok(ref s) if s.is_empty() => {
                 eprintln!("No slurp selection made");
-                std::process::exit(1);
+                None
             }
             Err(_) => {
                 eprintln!("Invalid slurp selection");
-                std::process::exit(1);
+                None
             }
-            Ok(sel) => window_geoms
-                .into_iter()
-                .find(|geom| geom.to_string() == sel)
-                .map_or_else(
-                    || (sel.parse().expect("Failed to parse slurp selection"), false),
-                    |sel| (sel, true),
-                ),
+            Ok(sel) => Some(
+                windows
+                    .into_iter()
+                    .find(|(geom, _)| geom.to_string() == sel)
+                    .map_or_else(
+                        || (sel.parse().expect("Failed to parse slurp selection"), None),
+                        |(geom, window)| (geom, Some(window)),
+                    ),
+            ),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intersection() {
+        let mon = SlurpGeom {
+            x: 1920,
+            y: 0,
+            w: 1920,
+            h: 1080,
+        };
+
+        let sel: SlurpGeom = "1800,100 200x50".parse().expect("valid geometry");
+        assert_eq!(
+            sel.intersection(&mon).map(|geom| geom.to_string()),
+            Some("1920,100 80x50".to_string())
+        );
+
+        let outside: SlurpGeom = "0,0 1920x1080".parse().expect("valid geometry");
+        assert!(
+            outside.intersection(&mon).is_none(),
+            "adjacent geometries should not intersect"
+        );
+    }
+
+    #[test]
+    fn test_union() {
+        let geoms = ["0,0 1920x1080", "-1080,-400 1080x1920", "1920,200 1280x720"]
+            .map(|geom| geom.parse::<SlurpGeom>().expect("valid geometry"));
+
+        assert_eq!(
+            geoms
+                .into_iter()
+                .reduce(|a, b| a.union(&b))
+                .map(|geom| geom.to_string()),
+            Some("-1080,-400 4280x1920".to_string())
+        );
+    }
+}
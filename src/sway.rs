@@ -2,7 +2,7 @@ use std::process::Command;
 
 use crate::{
     SlurpGeom, command_json,
-    monitor::{FocalMonitor, FocalMonitors, Rotation},
+    monitor::{FocalMonitor, FocalMonitors, FocalWindow, Rotation},
 };
 
 use serde_derive::Deserialize;
@@ -24,12 +24,24 @@ struct Rect {
     pub height: i32,
 }
 
+#[derive(Debug, Deserialize)]
+struct WindowProperties {
+    pub class: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct GetTreeWindowNode {
     pub rect: Rect,
     pub nodes: Vec<Self>,
+    #[serde(default)]
+    pub floating_nodes: Vec<Self>,
     // visible is only available in leaf (window) nodes
     pub visible: Option<bool>,
+    pub focused: bool,
+    pub name: Option<String>,
+    // app_id is only available for wayland windows, xwayland windows use window_properties
+    pub app_id: Option<String>,
+    pub window_properties: Option<WindowProperties>,
 }
 
 #[allow(clippy::used_underscore_items)]
@@ -41,6 +53,18 @@ impl GetTreeWindowNode {
         leaf_nodes
     }
 
+    /// recursively finds the focused node, including floating nodes
+    pub fn focused_node(&self) -> Option<&Self> {
+        if self.focused {
+            return Some(self);
+        }
+
+        self.nodes
+            .iter()
+            .chain(&self.floating_nodes)
+            .find_map(Self::focused_node)
+    }
+
     /// helper function for recursion
     fn _leaf_nodes<'a>(&'a self, leaf_nodes: &mut Vec<&'a Self>) {
         if self.nodes.is_empty() {
@@ -79,7 +103,7 @@ fn to_focal_monitor(mon: &GetOutput) -> FocalMonitor {
     }
 }
 
-fn window_geoms_cmd(cmd: &mut Command) -> Vec<SlurpGeom> {
+fn windows_cmd(cmd: &mut Command) -> Vec<(SlurpGeom, FocalWindow)> {
     let tree: GetTreeWindowNode = command_json(cmd);
 
     tree.leaf_nodes()
@@ -87,16 +111,28 @@ fn window_geoms_cmd(cmd: &mut Command) -> Vec<SlurpGeom> {
         .filter(|&node| node.visible == Some(true))
         .map(|win_node| {
             let rect = &win_node.rect;
-            SlurpGeom {
+            let geom = SlurpGeom {
                 x: rect.x,
                 y: rect.y,
                 w: rect.width,
                 h: rect.height,
-            }
+            };
+            (geom, to_focal_window(win_node))
         })
         .collect()
 }
 
+fn to_focal_window(node: &GetTreeWindowNode) -> FocalWindow {
+    FocalWindow {
+        class: node
+            .app_id
+            .clone()
+            .or_else(|| node.window_properties.as_ref()?.class.clone())
+            .unwrap_or_default(),
+        title: node.name.clone().unwrap_or_default(),
+    }
+}
+
 impl FocalMonitors for SwayMonitors {
     fn all(&self) -> Vec<FocalMonitor> {
         let monitors: Vec<GetOutput> = command_json(
@@ -123,12 +159,27 @@ impl FocalMonitors for SwayMonitors {
             .expect("no focused monitor")
     }
 
-    fn window_geoms(&self) -> Vec<SlurpGeom> {
-        window_geoms_cmd(
+    fn windows(&self) -> Vec<(SlurpGeom, FocalWindow)> {
+        windows_cmd(
             Command::new("swaymsg")
                 .arg("-t")
                 .arg("get_tree")
                 .arg("--raw"),
         )
     }
+
+    fn active_window(&self) -> Option<FocalWindow> {
+        let tree: GetTreeWindowNode = command_json(
+            Command::new("swaymsg")
+                .arg("-t")
+                .arg("get_tree")
+                .arg("--raw"),
+        );
+
+        let node = tree.focused_node()?;
+        // outputs and workspaces can also be focused
+        node.visible?;
+
+        Some(to_focal_window(node))
+    }
 }
@@ -0,0 +1,371 @@
+use std::{
+    fmt,
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local};
+use clap::CommandFactory;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    SlurpGeom,
+    cli::focal::{Cli, CommonArgs},
+    create_parent_dirs,
+    monitor::{FocalMonitor, FocalWindow},
+};
+
+/// iso8601 timestamp with second resolution
+pub const DEFAULT_TEMPLATE: &str = "{%Y-%m-%dT%H:%M:%S%:z}";
+
+#[derive(Debug)]
+pub struct TemplateError {
+    message: String,
+}
+
+impl TemplateError {
+    fn new(msg: &str) -> Self {
+        Self {
+            message: msg.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// metadata about a capture that can be used in filename templates
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct CaptureInfo {
+    pub area: String,
+    /// logical geometry of the capture in the form of "x,y wxh"
+    pub geometry: String,
+    pub monitor: String,
+    pub app: String,
+    pub title: String,
+    pub w: u32,
+    pub h: u32,
+}
+
+impl CaptureInfo {
+    pub fn new(area: &str) -> Self {
+        Self {
+            area: area.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// creates the info for a capture of the selection, which may be a window
+    pub fn selection(window: Option<FocalWindow>) -> Self {
+        window.map_or_else(
+            || Self::new("selection"),
+            |window| Self::new("window").window(window),
+        )
+    }
+
+    /// uses the class and title of the captured window for {app} and {title}
+    #[must_use]
+    pub fn window(self, window: FocalWindow) -> Self {
+        Self {
+            app: window.class,
+            title: window.title,
+            ..self
+        }
+    }
+
+    /// uses the name and logical size of the monitor
+    #[must_use]
+    pub fn monitor(self, mon: &FocalMonitor) -> Self {
+        Self {
+            monitor: mon.name.clone(),
+            geometry: format!("{},{} {}x{}", mon.x, mon.y, mon.w, mon.h),
+            ..self.dimensions(
+                u32::try_from(mon.w).unwrap_or_default(),
+                u32::try_from(mon.h).unwrap_or_default(),
+            )
+        }
+    }
+
+    /// uses the logical size of the geometry and the monitor it is on
+    #[must_use]
+    pub fn geometry(self, geom: &SlurpGeom) -> Self {
+        Self {
+            monitor: geom.monitor().map(|mon| mon.name).unwrap_or_default(),
+            geometry: geom.to_string(),
+            ..self.dimensions(
+                u32::try_from(geom.w).unwrap_or_default(),
+                u32::try_from(geom.h).unwrap_or_default(),
+            )
+        }
+    }
+
+    #[must_use]
+    pub const fn dimensions(mut self, w: u32, h: u32) -> Self {
+        self.w = w;
+        self.h = h;
+        self
+    }
+}
+
+/// a path containing strftime fields such as {%Y} and capture fields such as {monitor}
+#[derive(Debug, Clone)]
+pub struct FilenameTemplate {
+    template: String,
+    time: DateTime<Local>,
+}
+
+/// literal text or a field of a template
+#[derive(Debug, PartialEq, Eq)]
+enum Part<'a> {
+    Text(&'a str),
+    Field(&'a str),
+}
+
+/// prevent capture fields from creating unexpected directories
+fn sanitize(field: &str) -> String {
+    field.replace('/', "_").trim().to_string()
+}
+
+impl FilenameTemplate {
+    pub fn new(template: &str) -> Self {
+        Self {
+            template: template.to_string(),
+            time: Local::now(),
+        }
+    }
+
+    /// escapes a path so it is used as is, without expanding any fields
+    pub fn escape(path: &str) -> String {
+        path.replace('{', "{{")
+    }
+
+    /// splits the template into text and fields, where {{ is a literal {
+    fn parts(&self) -> Result<Vec<Part<'_>>, TemplateError> {
+        let mut parts = Vec::new();
+        let mut rest = self.template.as_str();
+
+        while let Some(start) = rest.find('{') {
+            parts.push(Part::Text(&rest[..start]));
+
+            if rest[start + 1..].starts_with('{') {
+                parts.push(Part::Text("{"));
+                rest = &rest[start + 2..];
+                continue;
+            }
+
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| TemplateError::new("Unclosed { in template"))?;
+            parts.push(Part::Field(&rest[start + 1..start + end]));
+            rest = &rest[start + end + 1..];
+        }
+        parts.push(Part::Text(rest));
+
+        Ok(parts)
+    }
+
+    /// if the template contains the field
+    fn uses(&self, field: &str) -> bool {
+        self.parts()
+            .is_ok_and(|parts| parts.contains(&Part::Field(field)))
+    }
+
+    /// expands all fields in the template, using n for the {n} counter
+    pub fn render(&self, info: &CaptureInfo, n: u32) -> Result<String, TemplateError> {
+        let mut rendered = String::new();
+
+        for part in self.parts()? {
+            let field = match part {
+                Part::Text(text) => {
+                    rendered.push_str(text);
+                    continue;
+                }
+                Part::Field(field) => field,
+            };
+
+            match field {
+                "area" => rendered.push_str(&sanitize(&info.area)),
+                "monitor" => rendered.push_str(&sanitize(&info.monitor)),
+                "app" => rendered.push_str(&sanitize(&info.app)),
+                "title" => rendered.push_str(&sanitize(&info.title)),
+                "w" => rendered.push_str(&info.w.to_string()),
+                "h" => rendered.push_str(&info.h.to_string()),
+                "n" => rendered.push_str(&n.to_string()),
+                strftime if strftime.starts_with('%') => {
+                    write!(rendered, "{}", self.time.format(strftime)).map_err(|_| {
+                        TemplateError::new(&format!("Invalid strftime field {{{strftime}}}"))
+                    })?;
+                }
+                _ => {
+                    return Err(TemplateError::new(&format!(
+                        "Unknown template field {{{field}}}"
+                    )));
+                }
+            }
+        }
+
+        Ok(rendered)
+    }
+
+    /// creates the template from FILE or the --template option, exiting if it is invalid
+    pub fn from_args(
+        args: &CommonArgs,
+        filename: Option<PathBuf>,
+        dir: &Path,
+        extension: &str,
+    ) -> Self {
+        Self::from_options(
+            args.template.as_deref(),
+            args.no_save,
+            filename,
+            dir,
+            extension,
+        )
+    }
+
+    /// creates the template from FILE, the template or a temporary file if not saving
+    /// FILE is used as is, only the template expands fields
+    pub fn from_options(
+        template: Option<&str>,
+        no_save: bool,
+        filename: Option<PathBuf>,
+        dir: &Path,
+        extension: &str,
+    ) -> Self {
+        let fname = format!("{}.{extension}", template.unwrap_or(DEFAULT_TEMPLATE));
+
+        let template = Self::new(&if no_save {
+            format!("/tmp/{fname}")
+        } else {
+            filename.map_or_else(
+                || dir.join(fname).to_string_lossy().to_string(),
+                |filename| Self::escape(&filename.to_string_lossy()),
+            )
+        });
+
+        if let Err(err) = template.validate() {
+            Cli::command()
+                .error(clap::error::ErrorKind::InvalidValue, err.to_string())
+                .exit()
+        }
+
+        template
+    }
+
+    /// appends -{field} to the filename if the template does not use the field,
+    /// so multiple captures from the same run have different names
+    #[must_use]
+    pub fn with_field(mut self, field: &str) -> Self {
+        if self.uses(field) {
+            return self;
+        }
+        let field = format!("{{{field}}}");
+
+        let path = Path::new(&self.template);
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let fname = path.extension().map_or_else(
+            || format!("{stem}-{field}"),
+            |ext| format!("{stem}-{field}.{}", ext.to_string_lossy()),
+        );
+        self.template = path.with_file_name(fname).to_string_lossy().to_string();
+        self
+    }
+
+    /// checks the template for errors before capturing
+    pub fn validate(&self) -> Result<(), TemplateError> {
+        self.render(&CaptureInfo::default(), 1).map(|_| ())
+    }
+
+    /// returns a path that does not exist yet, creating its parent directories
+    pub fn path(&self, info: &CaptureInfo) -> PathBuf {
+        let render = |n| PathBuf::from(self.render(info, n).expect("invalid filename template"));
+
+        let path = if self.uses("n") {
+            (1..u32::MAX)
+                .map(render)
+                .find(|path| !path.exists())
+                .expect("could not find an unused filename")
+        } else {
+            render(1)
+        };
+
+        create_parent_dirs(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info() -> CaptureInfo {
+        CaptureInfo {
+            area: "window".to_string(),
+            geometry: "0,0 1920x1080".to_string(),
+            monitor: "DP-1".to_string(),
+            app: "firefox".to_string(),
+            title: "a/b".to_string(),
+            w: 1920,
+            h: 1080,
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let template =
+            FilenameTemplate::new("{%Y}/{%m}/{area}-{monitor}-{app}-{title}-{w}x{h}-{n}");
+        let year = template.time.format("%Y").to_string();
+        let month = template.time.format("%m").to_string();
+
+        assert_eq!(
+            template.render(&info(), 3).expect("failed to render"),
+            format!("{year}/{month}/window-DP-1-firefox-a_b-1920x1080-3"),
+            "fields should be expanded and slashes in titles replaced"
+        );
+    }
+
+    #[test]
+    fn test_with_field() {
+        let template = FilenameTemplate::new("/pics/{%Y}.d/{app}.png").with_field("n");
+        assert_eq!(template.template, "/pics/{%Y}.d/{app}-{n}.png");
+
+        let template = FilenameTemplate::new("/pics/shot-{n}.png").with_field("n");
+        assert_eq!(
+            template.template, "/pics/shot-{n}.png",
+            "existing fields should be kept"
+        );
+
+        let template = FilenameTemplate::new("/pics/shot").with_field("monitor");
+        assert_eq!(template.template, "/pics/shot-{monitor}");
+    }
+
+    #[test]
+    fn test_escape() {
+        for path in ["/pics/shot{1}.png", "~/pics/{draft}.png", "/pics/{{n}}"] {
+            let template = FilenameTemplate::new(&FilenameTemplate::escape(path));
+            assert_eq!(
+                template.render(&info(), 3).expect("failed to render"),
+                path,
+                "escaped paths should be used as is"
+            );
+        }
+
+        let template =
+            FilenameTemplate::new(&FilenameTemplate::escape("/pics/{n}.png")).with_field("n");
+        assert_eq!(
+            template.render(&info(), 3).expect("failed to render"),
+            "/pics/{n}-3.png",
+            "fields should still be appended to escaped paths"
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(FilenameTemplate::new("{foo}").validate().is_err());
+        assert!(FilenameTemplate::new("{%Y").validate().is_err());
+        assert!(FilenameTemplate::new("{%Q}").validate().is_err());
+        assert!(FilenameTemplate::new(DEFAULT_TEMPLATE).validate().is_ok());
+    }
+}
@@ -0,0 +1,320 @@
+use std::{collections::HashMap, fmt, path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{clipboard::Clipboard, show_notification};
+
+#[derive(Debug)]
+pub struct UploadError {
+    message: String,
+}
+
+impl UploadError {
+    fn new(msg: &str) -> Self {
+        Self {
+            message: msg.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for UploadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// an http endpoint captures can be uploaded to, defined as [uploads.NAME] in the config file
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Upload {
+    pub url: String,
+    /// name of the multipart form field for the file, the file is sent as the raw body if not set
+    pub field: Option<String>,
+    /// additional multipart form fields
+    pub fields: HashMap<String, String>,
+    pub headers: HashMap<String, String>,
+    /// sent as a bearer token in the Authorization header
+    pub token: Option<String>,
+    /// json pointer to the url in the response, e.g. /data/url
+    pub json_pointer: Option<String>,
+    /// regex to extract the url from the response, using the first capture group if any
+    pub regex: Option<String>,
+}
+
+/// uploads that take longer than this are cancelled, so a stalled host cannot hang focal
+const TIMEOUT: Duration = Duration::from_mins(1);
+
+/// percent-encodes quotes and line breaks in a quoted header parameter, as browsers do (RFC 7578)
+fn escape_param(value: &str) -> String {
+    value
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// creates a multipart/form-data body, returning the boundary and the body
+fn multipart(
+    fields: &HashMap<String, String>,
+    field: &str,
+    filename: &str,
+    mime: &str,
+    content: &[u8],
+) -> (String, Vec<u8>) {
+    let boundary = format!(
+        "focal-{}",
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default()
+    );
+
+    let mut body = Vec::new();
+    for (name, value) in fields {
+        let name = escape_param(name);
+        body.extend(
+            format!(
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+            )
+            .as_bytes(),
+        );
+    }
+
+    let field = escape_param(field);
+    let filename = escape_param(filename);
+    body.extend(
+        format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"{field}\"; filename=\"{filename}\"\r\nContent-Type: {mime}\r\n\r\n"
+        )
+        .as_bytes(),
+    );
+    body.extend(content);
+    body.extend(format!("\r\n--{boundary}--\r\n").as_bytes());
+
+    (boundary, body)
+}
+
+impl Upload {
+    /// uploads the file, returning the url from the response
+    pub fn upload(&self, path: &Path, mime: &str) -> Result<String, UploadError> {
+        let content = std::fs::read(path).map_err(|err| {
+            UploadError::new(&format!("Failed to read {}: {err}", path.display()))
+        })?;
+
+        let agent: ureq::Agent = ureq::Agent::config_builder()
+            .timeout_global(Some(TIMEOUT))
+            .build()
+            .into();
+
+        let mut req = agent.post(&self.url);
+        for (name, value) in &self.headers {
+            req = req.header(name, value);
+        }
+        if let Some(token) = &self.token {
+            req = req.header("Authorization", &format!("Bearer {token}"));
+        }
+
+        let res = if let Some(field) = &self.field {
+            let filename = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let (boundary, body) = multipart(&self.fields, field, &filename, mime, &content);
+
+            req.content_type(&format!("multipart/form-data; boundary={boundary}"))
+                .send(&body[..])
+        } else {
+            req.content_type(mime).send(&content[..])
+        };
+
+        let body = res
+            .and_then(|mut res| res.body_mut().read_to_string())
+            .map_err(|err| UploadError::new(&format!("Failed to upload to {}: {err}", self.url)))?;
+
+        self.extract_url(&body)
+    }
+
+    /// uploads the file and copies the url, reporting any errors
+    pub fn upload_and_copy(
+        &self,
+        path: &Path,
+        mime: &str,
+        primary: bool,
+        notify: bool,
+    ) -> Option<String> {
+        match self.upload(path, mime) {
+            Ok(url) => {
+                Clipboard::new().primary(primary).text(&url).copy();
+                Some(url)
+            }
+            Err(err) => {
+                eprintln!("{err}");
+                if notify {
+                    show_notification(&err.to_string(), None);
+                }
+                None
+            }
+        }
+    }
+
+    /// extracts the url from the response body, using the whole body if there is no pointer or regex
+    pub fn extract_url(&self, body: &str) -> Result<String, UploadError> {
+        if let Some(pointer) = &self.json_pointer {
+            let json: serde_json::Value = serde_json::from_str(body)
+                .map_err(|err| UploadError::new(&format!("Invalid json response: {err}")))?;
+
+            return match json.pointer(pointer) {
+                Some(serde_json::Value::String(url)) => Ok(url.clone()),
+                Some(value) => Ok(value.to_string()),
+                None => Err(UploadError::new(&format!(
+                    "{pointer} not found in response: {body}"
+                ))),
+            };
+        }
+
+        if let Some(regex) = &self.regex {
+            let re = regex::Regex::new(regex)
+                .map_err(|err| UploadError::new(&format!("Invalid upload regex: {err}")))?;
+
+            return re
+                .captures(body)
+                .and_then(|caps| caps.get(1).or_else(|| caps.get(0)))
+                .map(|m| m.as_str().to_string())
+                .ok_or_else(|| {
+                    UploadError::new(&format!("{regex} did not match response: {body}"))
+                });
+        }
+
+        let url = body.trim();
+        if url.is_empty() {
+            return Err(UploadError::new("Upload response was empty"));
+        }
+        Ok(url.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+    };
+
+    /// serves a single request, returning the request and responding with the given body
+    fn stub_server(response: &'static str) -> (String, std::thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind stub server");
+        let url = format!(
+            "http://{}/upload",
+            listener.local_addr().expect("no address")
+        );
+
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().expect("failed to accept connection");
+            let mut reader = BufReader::new(stream);
+
+            let mut request = String::new();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).expect("failed to read request");
+                if let Some((name, value)) = line.split_once(':')
+                    && name.eq_ignore_ascii_case("content-length")
+                {
+                    content_length = value.trim().parse().expect("invalid content length");
+                }
+                request.push_str(&line);
+                if line == "\r\n" {
+                    break;
+                }
+            }
+
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).expect("failed to read body");
+            request.push_str(&String::from_utf8_lossy(&body));
+
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+                response.len()
+            )
+            .expect("failed to write response");
+
+            request
+        });
+
+        (url, handle)
+    }
+
+    #[test]
+    fn test_upload() {
+        let (url, server) = stub_server(r#"{"data": {"url": "https://example.com/a.png"}}"#);
+
+        let path = std::env::temp_dir().join(format!("focal-upload-{}.png", std::process::id()));
+        std::fs::write(&path, b"png data").expect("failed to write test file");
+
+        let upload = Upload {
+            url,
+            field: Some("file".to_string()),
+            fields: HashMap::from([("expires".to_string(), "1d".to_string())]),
+            headers: HashMap::from([("X-Api-Key".to_string(), "secret".to_string())]),
+            token: Some("token".to_string()),
+            json_pointer: Some("/data/url".to_string()),
+            ..Default::default()
+        };
+        let res = upload.upload(&path, "image/png");
+        std::fs::remove_file(&path).expect("failed to remove test file");
+
+        assert_eq!(res.expect("upload failed"), "https://example.com/a.png");
+
+        let request = server.join().expect("stub server failed").to_lowercase();
+        assert!(request.starts_with("post /upload"));
+        assert!(request.contains("x-api-key: secret"));
+        assert!(request.contains("authorization: bearer token"));
+        assert!(request.contains("content-type: multipart/form-data; boundary="));
+        assert!(request.contains("name=\"expires\"\r\n\r\n1d"));
+        assert!(request.contains("filename=\"focal-upload-"));
+        assert!(request.contains("content-type: image/png\r\n\r\npng data"));
+    }
+
+    #[test]
+    fn test_multipart_escaping() {
+        let (boundary, body) = multipart(
+            &HashMap::from([("a\"b".to_string(), "1".to_string())]),
+            "file",
+            "say \"hi\"\r\nX-Injected: 1.png",
+            "image/png",
+            b"png data",
+        );
+        let body = String::from_utf8(body).expect("body should be utf-8");
+
+        assert!(body.contains("name=\"a%22b\"\r\n\r\n1"));
+        assert!(body.contains("filename=\"say %22hi%22%0D%0AX-Injected: 1.png\"\r\n"));
+        assert!(
+            !body.contains("\r\nX-Injected"),
+            "line breaks in the filename should not start a new header"
+        );
+        assert!(body.ends_with(&format!("\r\n--{boundary}--\r\n")));
+    }
+
+    #[test]
+    fn test_extract_url() {
+        let upload = Upload {
+            regex: Some(r#"href="([^"]+)""#.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            upload
+                .extract_url(r#"<a href="https://example.com/a">"#)
+                .expect("regex should match"),
+            "https://example.com/a"
+        );
+        assert!(upload.extract_url("nothing").is_err());
+
+        assert_eq!(
+            Upload::default()
+                .extract_url("https://example.com/b\n")
+                .expect("body should be used"),
+            "https://example.com/b"
+        );
+    }
+}
@@ -3,16 +3,33 @@ use std::{path::PathBuf, process::Command, vec};
 
 use crate::{
     Rofi, SlurpGeom, check_programs,
-    cli::video::{CaptureArea, VideoArgs},
-    create_parent_dirs, focal_monitor, is_hyprland, iso8601_filename, show_notification,
+    cli::{
+        focal::{Cli, CopyMode},
+        video::{CaptureArea, VideoArgs},
+    },
+    clipboard::Clipboard,
+    config::Config,
+    focal_monitor, history,
+    hooks::{CaptureKind, HookContext, Hooks},
+    is_hyprland, show_notification,
+    template::{CaptureInfo, FilenameTemplate},
     wf_recorder::WfRecorder,
 };
+use clap::CommandFactory;
 use execute::Execute;
 
 #[derive(Serialize, Deserialize)]
 pub struct LockFile {
     pub video: PathBuf,
     pub rounding: Option<i64>,
+    pub info: CaptureInfo,
+    pub hooks: Hooks,
+    /// name of the upload endpoint, which is read from the config when stopping to keep tokens out of the lock file
+    pub upload: Option<String>,
+    pub profile: Option<String>,
+    pub primary: bool,
+    /// unix timestamp of when the recording started
+    pub started: u64,
 }
 
 impl LockFile {
@@ -52,32 +69,51 @@ pub struct Screencast {
     pub notify: bool,
     pub duration: Option<u64>,
     pub slurp: Option<String>,
-    pub output: PathBuf,
+    pub copy: Vec<CopyMode>,
+    pub primary: bool,
+    pub hooks: Hooks,
+    pub upload: Option<String>,
+    pub profile: Option<String>,
+    pub template: FilenameTemplate,
+}
+
+fn unix_time() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
 }
 
 impl Screencast {
-    fn capture(&self, mon: &str, filter: &str, rounding: Option<i64>) {
+    fn capture(&self, info: &CaptureInfo, mon: &str, filter: &str, rounding: Option<i64>) {
         ctrlc::set_handler(move || {
             Self::stop(false);
         })
         .expect("unable to set ctrl-c handler");
 
+        let output = self.template.path(info);
+
         // copy the video file to clipboard
-        Command::new("wl-copy")
-            .arg("--type")
-            .arg("text/uri-list")
-            .execute_input(&format!("file://{}", self.output.display()))
-            .expect("failed to copy video to clipboard");
+        Clipboard::new()
+            .primary(self.primary)
+            .modes(&self.copy, &output, "video/mp4")
+            .copy();
 
         // small delay before recording
         std::thread::sleep(std::time::Duration::from_millis(500));
 
         let lock = LockFile {
-            video: self.output.clone(),
+            video: output.clone(),
             rounding,
+            info: info.clone(),
+            hooks: self.hooks.clone(),
+            upload: self.upload.clone(),
+            profile: self.profile.clone(),
+            primary: self.primary,
+            started: unix_time(),
         };
 
-        WfRecorder::new(mon, self.output.clone())
+        WfRecorder::new(mon, output)
             .audio(self.audio.as_deref())
             .filter(filter)
             .record();
@@ -109,7 +145,17 @@ impl Screencast {
             .count()
             > 0;
 
-        if let Ok(LockFile { video, rounding }) = LockFile::read() {
+        if let Ok(LockFile {
+            video,
+            rounding,
+            info,
+            hooks,
+            upload,
+            profile,
+            primary,
+            started,
+        }) = LockFile::read()
+        {
             LockFile::remove();
 
             if is_hyprland()
@@ -119,18 +165,56 @@ impl Screencast {
                     .expect("unable to restore rounding");
             }
 
+            Self::wait_for_exit();
+
+            let duration = unix_time().saturating_sub(started);
+            history::record(CaptureKind::Video, &video, &info, Some(duration));
+
+            let url = Config::load(profile.as_deref())
+                .upload(upload.as_deref())
+                .and_then(|upload| upload.upload_and_copy(&video, "video/mp4", primary, notify));
+
+            let background = hooks.run(
+                &HookContext {
+                    kind: CaptureKind::Video,
+                    path: &video,
+                    info: &info,
+                    duration: Some(duration),
+                },
+                notify,
+            );
+
             // show notification with the video thumbnail
             if notify {
-                Self::notify(&video);
+                Self::notify(&video, url.as_deref());
             }
 
+            background.wait();
+
             return true;
         }
 
         is_killed
     }
 
-    fn notify(video: &PathBuf) {
+    /// waits for up to 5s for wf-recorder to finish writing the video
+    fn wait_for_exit() {
+        for _ in 0..50 {
+            let running = Command::new("pgrep")
+                .arg("-x")
+                .arg("wf-recorder")
+                .output()
+                .is_ok_and(|output| output.status.success());
+
+            if !running {
+                return;
+            }
+
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+    }
+
+    fn notify(video: &PathBuf, url: Option<&str>) {
         let thumb_path = PathBuf::from("/tmp/focal-thumbnail.jpg");
 
         if thumb_path.exists() {
@@ -153,18 +237,25 @@ impl Screencast {
 
         // show notifcation with the video thumbnail
         show_notification(
-            &format!("Video captured to {}", video.display()),
+            &url.map_or_else(
+                || format!("Video captured to {}", video.display()),
+                |url| format!("Video uploaded to {url}"),
+            ),
             Some(&thumb_path),
         );
     }
 
     pub fn selection(&self) {
-        let (geom, is_window) = SlurpGeom::prompt(self.slurp.as_deref());
+        let Some((geom, window)) = SlurpGeom::prompt(self.slurp.as_deref()) else {
+            std::process::exit(1);
+        };
+        let is_window = window.is_some();
         let (mon, filter) = geom.to_ffmpeg_geom();
+        let info = CaptureInfo::selection(window).geometry(&geom);
 
         let do_capture = |rounding: Option<i64>| {
             std::thread::sleep(std::time::Duration::from_secs(self.delay.unwrap_or(0)));
-            self.capture(&mon, &filter, rounding);
+            self.capture(&info, &mon, &filter, rounding);
         };
 
         if is_hyprland() && is_window && self.no_rounded_windows {
@@ -189,7 +280,12 @@ impl Screencast {
 
         let mon = focal_monitor().focused();
         let transpose = mon.rotation.ffmpeg_transpose();
-        self.capture(&mon.name, &transpose, None);
+        self.capture(
+            &CaptureInfo::new("monitor").monitor(&mon),
+            &mon.name,
+            &transpose,
+            None,
+        );
     }
 
     pub fn rofi(&mut self, theme: Option<&PathBuf>) {
@@ -270,7 +366,10 @@ impl Screencast {
     }
 }
 
-pub fn main(args: VideoArgs) {
+pub fn main(mut args: VideoArgs) {
+    let config = Config::load(args.common_args.profile.as_deref());
+    config.apply_video(&mut args);
+
     // stop any currently recording videos
     if Screencast::stop(!args.common_args.no_notify) {
         println!("Stopping previous recording...");
@@ -282,29 +381,48 @@ pub fn main(args: VideoArgs) {
         return;
     }
 
+    // wf-recorder always records the cursor
+    if !args.common_args.show_cursor(true) {
+        Cli::command()
+            .error(
+                clap::error::ErrorKind::ArgumentConflict,
+                "Hiding the cursor is not supported by wf-recorder.",
+            )
+            .exit()
+    }
+
+    // the video is copied before it is recorded
+    if args.common_args.copy.contains(&CopyMode::Image) {
+        Cli::command()
+            .error(
+                clap::error::ErrorKind::InvalidValue,
+                "Copying the video contents is not supported, use --copy uri instead.",
+            )
+            .exit()
+    }
+
     // check if all required programs are installed
     check_programs(&args.required_programs());
 
-    let fname = format!("{}.mp4", iso8601_filename());
+    // the upload is only read from the config once recording stops, so check it exists beforehand
+    config.upload(args.common_args.upload.as_deref());
 
-    let output = if args.common_args.no_save {
-        PathBuf::from(format!("/tmp/{fname}"))
-    } else {
-        create_parent_dirs(args.filename.unwrap_or_else(|| {
-            dirs::video_dir()
-                .expect("could not get $XDG_VIDEOS_DIR")
-                .join(format!("Screencasts/{fname}"))
-        }))
-    };
+    let template =
+        FilenameTemplate::from_args(&args.common_args, args.filename, &config.video_dir(), "mp4");
 
     let mut screencast = Screencast {
-        output,
+        template,
         icons: !args.rofi_args.no_icons,
         notify: !args.common_args.no_notify,
         no_rounded_windows: args.common_args.no_rounded_windows,
         delay: args.common_args.delay,
         duration: args.duration,
         audio: args.audio,
+        copy: args.common_args.copy_modes(CopyMode::Uri),
+        primary: args.common_args.primary,
+        hooks: Hooks::from_args(&args.common_args, &config, CaptureKind::Video),
+        upload: args.common_args.upload,
+        profile: args.common_args.profile,
         slurp: args.common_args.slurp,
     };
 
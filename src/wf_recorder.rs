@@ -3,6 +3,7 @@ use std::{
     process::{Command, Stdio},
 };
 
+/// wf-recorder always overlays the cursor on recordings
 #[derive(Default)]
 pub struct WfRecorder {
     monitor: String,